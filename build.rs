@@ -502,6 +502,13 @@ fn main() {
     add_llvm_path();
     add_clang_path();
 
+    // Let the crate know which version the bindings were generated for, to
+    // compare with the version of the library that is loaded at runtime
+    println!(
+        "cargo:rustc-env=RDFOX_VERSION_EXPECTED={}",
+        *RDFOX_VERSION_EXPECTED
+    );
+
     let file_name = download_rdfox().expect("cargo:warning=Could not download RDFox");
     unzip_rdfox(file_name, rdfox_archive_name());
 
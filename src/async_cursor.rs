@@ -0,0 +1,69 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    crate::{AnswerRow, Cursor, DataStoreConnection, Parameters, Statement, Transaction},
+    ekg_namespace::consts::LOG_TARGET_DATABASE,
+    futures::Stream,
+    std::sync::Arc,
+    tokio::sync::mpsc,
+};
+
+/// The number of rows that are read ahead of the consumer of the stream
+const QUERY_STREAM_BUFFER_SIZE: usize = 64;
+
+impl DataStoreConnection {
+    /// Evaluate the given query and return its answers as a `Stream`, for
+    /// use in async code such as web services.
+    ///
+    /// Note that this is not natively async: the RDFox API is synchronous so
+    /// the cursor is opened and advanced in a read-only transaction on
+    /// Tokio's blocking thread pool (via `spawn_blocking`), hence this has to
+    /// be called from within a Tokio runtime. At most a small number of rows
+    /// is read ahead of the consumer, so a slow consumer slows down the
+    /// cursor rather than filling up memory. Dropping the stream stops the
+    /// cursor and closes it.
+    pub fn query_stream(
+        self: &Arc<Self>,
+        statement: &Statement,
+        parameters: &Parameters,
+    ) -> Result<impl Stream<Item = Result<AnswerRow, ekg_error::Error>>, ekg_error::Error> {
+        let (sender, receiver) = mpsc::channel(QUERY_STREAM_BUFFER_SIZE);
+        let connection = self.clone();
+        let statement = statement.clone();
+        let parameters = parameters.try_clone()?;
+
+        tokio::task::spawn_blocking(move || {
+            let result = Cursor::create(&connection, &parameters, &statement).and_then(
+                |mut cursor| {
+                    Transaction::begin_read_only(&connection)?.execute_and_rollback(|ref tx| {
+                        cursor.consume(tx, usize::MAX, |row| {
+                            let answer = AnswerRow::try_from(row)?;
+                            sender.blocking_send(Ok(answer)).map_err(|_| {
+                                ekg_error::Error::Exception {
+                                    action:  "streaming query answers".to_string(),
+                                    message: "the stream was dropped".to_string(),
+                                }
+                            })
+                        })
+                    })
+                },
+            );
+            if let Err(err) = result {
+                if sender.is_closed() {
+                    tracing::debug!(
+                        target: LOG_TARGET_DATABASE,
+                        conn = connection.number,
+                        "Query stream was dropped before the last answer"
+                    );
+                } else {
+                    let _ = sender.blocking_send(Err(err));
+                }
+            }
+        });
+
+        Ok(futures::stream::unfold(receiver, |mut receiver| {
+            async move { receiver.recv().await.map(|item| (item, receiver)) }
+        }))
+    }
+}
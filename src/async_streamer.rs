@@ -0,0 +1,100 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    crate::{DataStoreConnection, Statement},
+    mime::Mime,
+    std::{
+        io::{ErrorKind, Write},
+        sync::Arc,
+    },
+    tokio::{
+        io::{AsyncWrite, AsyncWriteExt},
+        sync::mpsc,
+    },
+};
+
+/// A `Write` that sends everything that RDFox writes to it, in chunks, over
+/// a bounded channel to the async side. When the channel is full the RDFox
+/// callback blocks, and when the receiving side is gone every write fails so
+/// that RDFox aborts the evaluation.
+struct ChannelWriter {
+    sender: mpsc::Sender<Vec<u8>>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.sender.blocking_send(buf.to_vec()).map_err(|_| {
+            std::io::Error::new(
+                ErrorKind::BrokenPipe,
+                "the receiving side of the export was dropped",
+            )
+        })?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
+
+impl DataStoreConnection {
+    /// The async counterpart of
+    /// [`evaluate_to_stream`](DataStoreConnection::evaluate_to_stream):
+    /// evaluate the given statement and write its output, in the given
+    /// format, to an async writer such as an HTTP response body, without
+    /// buffering the whole output.
+    ///
+    /// RDFox is run on Tokio's blocking thread pool and passes its output
+    /// over a channel that holds at most `channel_capacity` chunks, so a
+    /// larger capacity trades memory for throughput. If writing to `writer`
+    /// fails, the channel is dropped and RDFox aborts the evaluation.
+    ///
+    /// Returns the number of solutions.
+    pub async fn evaluate_to_async_writer<W>(
+        self: &Arc<Self>,
+        writer: &mut W,
+        statement: &Statement,
+        mime_type: &Mime,
+        channel_capacity: usize,
+    ) -> Result<u64, ekg_error::Error>
+        where
+            W: AsyncWrite + Unpin,
+    {
+        let (sender, mut receiver) = mpsc::channel::<Vec<u8>>(channel_capacity.max(1));
+        let connection = self.clone();
+        let statement = statement.clone();
+        let mime_type = mime_type.clone();
+
+        let evaluation = tokio::task::spawn_blocking(move || {
+            connection
+                .evaluate_to_stream(
+                    ChannelWriter { sender },
+                    &statement,
+                    &mime_type,
+                    None,
+                    None,
+                )
+                .map(|streamer| streamer.number_of_solutions())
+        });
+
+        let mut write_result = Ok(());
+        while let Some(chunk) = receiver.recv().await {
+            if let Err(err) = writer.write_all(chunk.as_slice()).await {
+                write_result = Err(err);
+                break;
+            }
+        }
+        // Dropping the receiver makes any further writes by RDFox fail
+        drop(receiver);
+
+        let number_of_solutions = evaluation.await.map_err(|err| {
+            ekg_error::Error::Exception {
+                action:  "exporting to an async writer".to_string(),
+                message: err.to_string(),
+            }
+        });
+        write_result?;
+        let number_of_solutions = number_of_solutions??;
+        writer.flush().await?;
+        Ok(number_of_solutions)
+    }
+}
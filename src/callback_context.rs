@@ -0,0 +1,59 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use std::{
+    ffi::c_void,
+    ops::{Deref, DerefMut},
+};
+
+/// The context of the read, write and flush callbacks that RDFox calls
+/// during an FFI call, wrapping the state of an
+/// [`Importer`](crate::importer::Importer),
+/// [`Exporter`](crate::exporter::Exporter) or [`Streamer`](crate::Streamer).
+///
+/// The context is always boxed so that its address, which we hand to RDFox
+/// as the context of the stream, is stable for the duration of the call.
+/// The callbacks are called from RDFox's C++ code so they must never panic:
+/// they [`fail`](Self::fail) instead, which remembers the I/O error and tells
+/// RDFox to abort, after which the caller reports the error that
+/// [`take_error`](Self::take_error) returns.
+pub(crate) struct CallbackContext<T> {
+    state:      T,
+    /// The first I/O error that occurred within one of the callbacks.
+    last_error: Option<std::io::Error>,
+}
+
+impl<T> CallbackContext<T> {
+    pub(crate) fn new(state: T) -> Box<Self> { Box::new(Self { state, last_error: None }) }
+
+    /// The pointer to pass to RDFox as the `context` of the stream.
+    pub(crate) fn as_context(&mut self) -> *mut c_void { self as *mut Self as *mut c_void }
+
+    /// Get the context back from the pointer that RDFox passes to a
+    /// callback, which must have been obtained with
+    /// [`as_context`](Self::as_context) from a context that's still alive.
+    pub(crate) unsafe fn from_context<'a>(context: *mut c_void) -> &'a mut Self {
+        &mut *(context as *mut Self)
+    }
+
+    /// Remember the given error, unless an earlier one was already
+    /// remembered, and return false for the callback to tell RDFox to abort.
+    pub(crate) fn fail(&mut self, err: std::io::Error) -> bool {
+        self.last_error.get_or_insert(err);
+        false
+    }
+
+    pub(crate) fn take_error(&mut self) -> Option<std::io::Error> { self.last_error.take() }
+
+    pub(crate) fn into_inner(self: Box<Self>) -> T { self.state }
+}
+
+impl<T> Deref for CallbackContext<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target { &self.state }
+}
+
+impl<T> DerefMut for CallbackContext<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.state }
+}
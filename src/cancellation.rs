@@ -0,0 +1,156 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    crate::{
+        database_call,
+        exception::timeout_error,
+        rdfox_api::CDataStoreConnection_interrupt,
+        DataStoreConnection,
+    },
+    ekg_namespace::consts::LOG_TARGET_DATABASE,
+    lazy_static::lazy_static,
+    std::{
+        collections::BTreeMap,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc,
+            Condvar,
+            Mutex,
+        },
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+/// A `CancellationToken` can be used from any thread, for instance a
+/// watchdog, to interrupt the operation (such as the evaluation of a query)
+/// that is currently running on a [`DataStoreConnection`], which then
+/// fails with an error.
+///
+/// See [`DataStoreConnection::cancellation_token`].
+#[derive(Debug)]
+pub struct CancellationToken {
+    connection: Arc<DataStoreConnection>,
+    cancelled:  AtomicBool,
+}
+
+impl CancellationToken {
+    pub(crate) fn new(connection: &Arc<DataStoreConnection>) -> Arc<Self> {
+        Arc::new(Self { connection: connection.clone(), cancelled: AtomicBool::new(false) })
+    }
+
+    /// Interrupt whatever is running on the connection right now.
+    pub fn cancel(&self) -> Result<(), ekg_error::Error> {
+        self.cancelled.store(true, Ordering::Relaxed);
+        database_call!(
+            format!("Interrupting {}", self.connection).as_str(),
+            CDataStoreConnection_interrupt(self.connection.inner)
+        )
+    }
+
+    /// Return true if [`cancel`](Self::cancel) has been called.
+    pub fn is_cancelled(&self) -> bool { self.cancelled.load(Ordering::Relaxed) }
+}
+
+lazy_static! {
+    /// The one thread that interrupts the statements that ran out of time,
+    /// started on first use.
+    static ref WATCHDOG: Arc<Watchdog> = Watchdog::start();
+}
+
+/// Keeps the tokens of the statements that are running with a timeout, by
+/// deadline, and cancels each one that's still there at its deadline.
+#[derive(Default)]
+struct Watchdog {
+    deadlines: Mutex<BTreeMap<(Instant, u64), Arc<CancellationToken>>>,
+    changed:   Condvar,
+    /// Keeps statements with the same deadline apart
+    sequence:  AtomicU64,
+}
+
+impl Watchdog {
+    fn start() -> Arc<Self> {
+        let watchdog = Arc::new(Self::default());
+        let watching = watchdog.clone();
+        thread::Builder::new()
+            .name("rdfox-watchdog".to_string())
+            .spawn(move || watching.run())
+            .expect("could not start the statement watchdog thread");
+        watchdog
+    }
+
+    fn watch(&self, deadline: Instant, token: Arc<CancellationToken>) -> (Instant, u64) {
+        let key = (deadline, self.sequence.fetch_add(1, Ordering::Relaxed));
+        self.deadlines.lock().unwrap().insert(key, token);
+        self.changed.notify_one();
+        key
+    }
+
+    /// Stop watching the given statement. Since tokens are only cancelled
+    /// while holding the lock, it's not cancelled anymore after this returns.
+    fn unwatch(&self, key: &(Instant, u64)) { self.deadlines.lock().unwrap().remove(key); }
+
+    fn run(&self) {
+        let mut deadlines = self.deadlines.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            while let Some(entry) = deadlines.first_entry() {
+                if entry.key().0 > now {
+                    break;
+                }
+                let token = entry.remove();
+                tracing::warn!(
+                    target: LOG_TARGET_DATABASE,
+                    "Interrupting the statement on {} that ran out of time",
+                    token.connection
+                );
+                if let Err(err) = token.cancel() {
+                    tracing::error!(
+                        target: LOG_TARGET_DATABASE,
+                        "Could not interrupt statement: {err}"
+                    );
+                }
+            }
+            let next_deadline = deadlines.keys().next().map(|(deadline, _)| *deadline);
+            deadlines = match next_deadline {
+                Some(deadline) => {
+                    self.changed
+                        .wait_timeout(deadlines, deadline.saturating_duration_since(now))
+                        .unwrap()
+                        .0
+                }
+                None => self.changed.wait(deadlines).unwrap(),
+            };
+        }
+    }
+}
+
+/// Run `f`, which evaluates a statement on the given connection, and
+/// interrupt it if it's still running after the given timeout, in which
+/// case an error is returned for which [`is_timeout`](crate::is_timeout) is
+/// true.
+///
+/// A statement that completes even though it was interrupted just then
+/// keeps its result.
+pub(crate) fn run_with_timeout<T, E, F>(
+    connection: &Arc<DataStoreConnection>,
+    timeout: Option<Duration>,
+    f: F,
+) -> Result<T, E>
+    where
+        E: From<ekg_error::Error>,
+        F: FnOnce() -> Result<T, E>,
+{
+    let Some(timeout) = timeout else {
+        return f();
+    };
+    let token = CancellationToken::new(connection);
+    let key = WATCHDOG.watch(Instant::now() + timeout, token.clone());
+    let result = f();
+    WATCHDOG.unwatch(&key);
+    match result {
+        Err(_) if token.is_cancelled() => Err(timeout_error(timeout).into()),
+        result => result,
+    }
+}
@@ -56,7 +56,7 @@ impl<'a> ClassReport<'a> {
         tx: &Arc<Transaction>,
         graph_connection: &GraphConnection,
     ) -> Result<usize, ekg_error::Error> {
-        let graph = graph_connection.graph.as_display_iri();
+        let graph = graph_connection.graph.graph().as_display_iri();
         let prefixes = Namespaces::builder()
             .declare(self.0.namespace.clone())
             .build()?;
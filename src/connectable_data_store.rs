@@ -4,13 +4,22 @@
 use {
     ::r2d2::{ManageConnection, Pool},
     crate::{DataStore, DataStoreConnection, ServerConnection},
-    std::sync::{
-        Arc,
-        atomic::{AtomicBool, Ordering},
-    }
-    ,
+    std::{
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        time::Duration,
+    },
 };
 
+/// A pool of [`DataStoreConnection`]s to one [`DataStore`], see
+/// [`DataStore::pool_for`] and [`ConnectableDataStore::build_pool_with`].
+///
+/// Use `pool.get()` to check out a connection, which blocks until one is
+/// available or the pool's connection timeout expires.
+pub type ConnectionPool = Pool<ConnectableDataStore>;
+
 /// A pool-able connectable [`DataStore`]
 pub struct ConnectableDataStore {
     data_store: Arc<DataStore>,
@@ -36,12 +45,27 @@ impl ConnectableDataStore {
     }
 
     /// Build an `r2d2::Pool` for the given `DataStore` and `ServerConnection`
-    pub fn build_pool(self) -> Result<Pool<ConnectableDataStore>, ekg_error::Error> {
+    pub fn build_pool(self) -> Result<ConnectionPool, ekg_error::Error> {
         let cds = Pool::builder()
             .max_size(self.server_connection.get_number_of_threads()?)
             .build(self)?;
         Ok(cds)
     }
+
+    /// Build an `r2d2::Pool` with at most `max_size` connections, where
+    /// checking out a connection fails with an error if none became
+    /// available within `connection_timeout`.
+    pub fn build_pool_with(
+        self,
+        max_size: u32,
+        connection_timeout: Duration,
+    ) -> Result<ConnectionPool, ekg_error::Error> {
+        let cds = Pool::builder()
+            .max_size(max_size)
+            .connection_timeout(connection_timeout)
+            .build(self)?;
+        Ok(cds)
+    }
 }
 
 impl ManageConnection for ConnectableDataStore {
@@ -53,7 +77,20 @@ impl ManageConnection for ConnectableDataStore {
             .connect_to_data_store(&self.data_store)
     }
 
-    fn is_valid(&self, _conn: &mut Self::Connection) -> Result<(), Self::Error> { Ok(()) }
+    /// Called by the pool when a connection is checked out: roll back any
+    /// transaction that the previous user of the connection left open so
+    /// that every user starts with a clean connection.
+    fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        if let Some(tx) = conn.active_transaction() {
+            tracing::warn!(
+                target: ekg_namespace::consts::LOG_TARGET_DATABASE,
+                conn = conn.number,
+                "Rolling back {tx} that was left open on a pooled connection"
+            );
+            tx.rollback()?;
+        }
+        Ok(())
+    }
 
     fn has_broken(&self, _conn: &mut Self::Connection) -> bool {
         self.release_on_return_to_pool.load(Ordering::Relaxed)
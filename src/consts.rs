@@ -0,0 +1,42 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {ekg_namespace::Namespace, lazy_static::lazy_static, mime::Mime};
+
+/// The IRI of `rdf:type`, the predicate that SPARQL and Turtle abbreviate
+/// as `a`.
+pub const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+lazy_static! {
+    /// SPARQL 1.1 Query Results CSV format, as supported by RDFox for the
+    /// answers of SELECT queries.
+    pub static ref SPARQL_RESULTS_CSV: Mime = "text/csv".parse().unwrap();
+    /// SPARQL 1.1 Query Results TSV format, as supported by RDFox for the
+    /// answers of SELECT queries.
+    pub static ref SPARQL_RESULTS_TSV: Mime = "text/tab-separated-values".parse().unwrap();
+    /// N-Triples, the line-based format for triples, used for the answers of
+    /// CONSTRUCT queries.
+    pub static ref APPLICATION_N_TRIPLES: Mime = "application/n-triples".parse().unwrap();
+    /// N-Quads, the line-based format for quads, used for the answers of
+    /// SELECT queries that project four variables: subject, predicate,
+    /// object and graph (see [`Statement::nquads_query`](crate::Statement::nquads_query)).
+    pub static ref APPLICATION_N_QUADS: Mime = "application/n-quads".parse().unwrap();
+    /// TriG, the Turtle extension for datasets, in which the triples of each
+    /// named graph are written in a `GRAPH <iri> { ... }` block next to the
+    /// triples of the default graph.
+    pub static ref APPLICATION_TRIG: Mime = "application/trig".parse().unwrap();
+    /// RDFox's Datalog syntax, for importing and exporting rules.
+    pub static ref APPLICATION_X_DATALOG: Mime = "application/x.datalog".parse().unwrap();
+}
+
+lazy_static! {
+    /// The Dublin Core Metadata Initiative terms namespace
+    pub static ref PREFIX_DCTERMS: Namespace =
+        Namespace::declare_from_str("dcterms:", "http://purl.org/dc/terms/").unwrap();
+    /// The Friend of a Friend (FOAF) namespace
+    pub static ref PREFIX_FOAF: Namespace =
+        Namespace::declare_from_str("foaf:", "http://xmlns.com/foaf/0.1/").unwrap();
+    /// The Shapes Constraint Language (SHACL) namespace
+    pub static ref PREFIX_SH: Namespace =
+        Namespace::declare_from_str("sh:", "http://www.w3.org/ns/shacl#").unwrap();
+}
@@ -0,0 +1,25 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use super::CursorRow;
+
+/// An owned copy of the bindings of a [`CursorRow`], which (unlike a
+/// `CursorRow`) can outlive the cursor and be sent to another thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnswerRow {
+    /// The number of times this answer occurs in the result
+    pub multiplicity: usize,
+    /// The lexical form of the value of each column, `None` if unbound
+    pub values:       Vec<Option<String>>,
+}
+
+impl<'a> TryFrom<&CursorRow<'a>> for AnswerRow {
+    type Error = ekg_error::Error;
+
+    fn try_from(row: &CursorRow<'a>) -> Result<Self, Self::Error> {
+        let values = (0..row.opened.arity)
+            .map(|term_index| row.lexical_form(term_index))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { multiplicity: *row.multiplicity, values })
+    }
+}
@@ -3,6 +3,7 @@
 
 use {
     crate::{
+        cancellation::run_with_timeout,
         database_call,
         DataStoreConnection,
         Parameters,
@@ -13,12 +14,22 @@ use {
     ekg_namespace::consts::LOG_TARGET_DATABASE,
     std::{ffi::CString, fmt::Debug, ptr, sync::Arc}
     ,
-    super::{CursorRow, OpenedCursor},
+    super::{CursorRow, OpenedCursor, ResultSet},
 };
 
 /// A Cursor handles a query result.
 ///
 /// [RDFox documentation](https://docs.oxfordsemantic.tech/apis.html#cursors)
+///
+/// # Thread safety
+///
+/// A `Cursor` is `Send` but not `Sync`. An RDFox cursor is bound to its
+/// connection rather than to the thread that created it, so it can be moved
+/// to another thread (as long as it's used with a transaction on the same
+/// connection), but it keeps its own position in the answers: advancing the
+/// same cursor from two threads at once would race, hence it cannot be
+/// shared. The same goes for an [`OpenedCursor`], which borrows its cursor
+/// and is therefore neither `Send` nor `Sync`.
 #[derive(Debug)]
 pub struct Cursor {
     pub inner: *mut CCursor,
@@ -26,14 +37,24 @@ pub struct Cursor {
     statement: Statement,
 }
 
+// The CCursor pointer is only ever used through `&mut self` or through an
+// `OpenedCursor`, which cannot leave the thread it was created on, see above.
+unsafe impl Send for Cursor {}
+
 impl Drop for Cursor {
     fn drop(&mut self) {
-        unsafe {
-            if !self.inner.is_null() {
-                CCursor_destroy(self.inner);
-                self.inner = ptr::null_mut();
-                tracing::debug!(target: LOG_TARGET_DATABASE, "Dropped cursor");
+        if !self.inner.is_null() {
+            if cfg!(debug_assertions) {
+                if let Some(tx) = self.connection.active_transaction() {
+                    tracing::warn!(
+                        target: LOG_TARGET_DATABASE,
+                        conn = self.connection.number,
+                        "Dropping cursor while {tx} is still open, consider calling close()"
+                    );
+                }
             }
+            self.destroy();
+            tracing::debug!(target: LOG_TARGET_DATABASE, "Dropped cursor");
         }
     }
 }
@@ -47,7 +68,7 @@ impl Cursor {
     ) -> Result<Self, ekg_error::Error> {
         assert!(!connection.inner.is_null());
         let mut c_cursor: *mut CCursor = ptr::null_mut();
-        let c_query = CString::new(statement.text.as_str()).unwrap();
+        let c_query = CString::new(statement.text.as_str())?;
         let c_query_len = c_query.as_bytes().len();
         tracing::trace!(
             target: LOG_TARGET_DATABASE,
@@ -77,12 +98,53 @@ impl Cursor {
         Ok(cursor)
     }
 
+    /// Destroy the underlying RDFox cursor now rather than whenever the
+    /// `Cursor` happens to be dropped, for deterministic cleanup on
+    /// long-lived (pooled) connections.
+    pub fn close(mut self) -> Result<(), ekg_error::Error> {
+        self.destroy();
+        tracing::debug!(target: LOG_TARGET_DATABASE, "Closed cursor");
+        Ok(())
+    }
+
+    /// Return true if the underlying RDFox cursor has been destroyed.
+    pub fn is_closed(&self) -> bool { self.inner.is_null() }
+
+    fn destroy(&mut self) {
+        if !self.inner.is_null() {
+            unsafe { CCursor_destroy(self.inner) };
+            self.inner = ptr::null_mut();
+        }
+    }
+
     pub fn sparql_string(&self) -> &str { self.statement.text.as_str() }
 
     pub fn count(&mut self, tx: &Arc<Transaction>) -> Result<usize, ekg_error::Error> {
         self.consume(tx, 1000000000, |_row| Ok(()))
     }
 
+    /// Read all answers into memory, resolved into terms, together with the
+    /// names of the answer variables.
+    pub fn result_set(
+        &mut self,
+        tx: &Arc<Transaction>,
+        max_row: usize,
+    ) -> Result<ResultSet, ekg_error::Error> {
+        let mut result_set = ResultSet::default();
+        self.consume(tx, max_row, |row| {
+            if result_set.variables.is_empty() {
+                result_set.variables = row.opened.headers()?;
+            }
+            let values = row.opened.resolve_row()?;
+            for _ in 1..*row.multiplicity {
+                result_set.rows.push(values.clone());
+            }
+            result_set.rows.push(values);
+            Ok::<(), ekg_error::Error>(())
+        })?;
+        Ok(result_set)
+    }
+
     #[tracing::instrument(
     target = "database",
     skip_all,
@@ -91,6 +153,23 @@ impl Cursor {
     )
     )]
     pub fn consume<T, E>(
+        &mut self,
+        tx: &Arc<Transaction>,
+        max_row: usize,
+        f: T,
+    ) -> Result<usize, E>
+        where
+            T: FnMut(&CursorRow) -> Result<(), E>,
+            E: From<ekg_error::Error> + Debug,
+    {
+        let connection = self.connection.clone();
+        let timeout = self.statement.timeout;
+        run_with_timeout(&connection, timeout, || {
+            self.consume_rows(tx, max_row, f)
+        })
+    }
+
+    fn consume_rows<T, E>(
         &mut self,
         tx: &Arc<Transaction>,
         max_row: usize,
@@ -100,6 +179,13 @@ impl Cursor {
             T: FnMut(&CursorRow) -> Result<(), E>,
             E: From<ekg_error::Error> + Debug,
     {
+        if self.is_closed() {
+            return Err(ekg_error::Error::Exception {
+                action:  "consuming a cursor".to_string(),
+                message: "the cursor has been closed".to_string(),
+            }
+            .into());
+        }
         let sparql_str = self.statement.text.clone();
         let (mut opened_cursor, mut multiplicity) = OpenedCursor::new(self, tx.clone())?;
         let mut rowid = 0_usize;
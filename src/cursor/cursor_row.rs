@@ -2,7 +2,12 @@
 //---------------------------------------------------------------
 
 use {
-    crate::{database_call, OpenedCursor, rdfox_api::CCursor_appendResourceLexicalForm},
+    crate::{
+        cursor::opened_cursor::INITIAL_LEXICAL_FORM_BUFFER_SIZE,
+        database_call,
+        OpenedCursor,
+        rdfox_api::CCursor_appendResourceLexicalForm,
+    },
     ekg_namespace::{
         consts::LOG_TARGET_DATABASE,
         DataType,
@@ -87,6 +92,18 @@ impl<'a> CursorRow<'a> {
         Literal::from_type_and_c_buffer(data_type, &buffer)
     }
 
+    /// Get the lexical form of the term with the given term index in the
+    /// current row as a plain string, without interpreting its datatype.
+    ///
+    /// Returns `None` if the variable is unbound in the current row.
+    pub fn lexical_form(&self, term_index: usize) -> Result<Option<String>, ekg_error::Error> {
+        let mut buffer = vec![0u8; INITIAL_LEXICAL_FORM_BUFFER_SIZE];
+        Ok(self
+            .opened
+            .lexical_form_into(term_index, &mut buffer)?
+            .map(|(_, lexical_form)| lexical_form.to_string()))
+    }
+
     /// Get the value in lexical form of a term in the current solution /
     /// current row with the given term index.
     pub fn lexical_value(&self, term_index: usize) -> Result<Option<Literal>, ekg_error::Error> {
@@ -1,9 +1,19 @@
 // Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
 //---------------------------------------------------------------
 
-pub use {cursor::Cursor, cursor_row::CursorRow, opened_cursor::OpenedCursor};
+pub use {
+    answer_row::AnswerRow,
+    cursor::Cursor,
+    cursor_row::CursorRow,
+    opened_cursor::OpenedCursor,
+    result_set::ResultSet,
+    triple_cursor::{Triple, TripleCursor},
+};
 
+mod answer_row;
 #[allow(clippy::module_inception)]
 mod cursor;
 mod cursor_row;
 mod opened_cursor;
+mod result_set;
+mod triple_cursor;
@@ -8,16 +8,22 @@ use {
         rdfox_api::{
             CCursor,
             CCursor_advance,
+            CCursor_appendResourceLexicalForm,
             CCursor_getAnswerVariableName,
             CCursor_getArity,
             CCursor_open,
         },
+        Term,
         Transaction,
     },
-    ekg_namespace::consts::LOG_TARGET_DATABASE,
-    std::{ptr, sync::Arc},
+    ekg_namespace::{consts::LOG_TARGET_DATABASE, DataType},
+    std::{cell::OnceCell, ptr, sync::Arc},
 };
 
+/// The initial size of the buffer that receives the lexical forms of
+/// resources, which is grown when RDFox needs more room.
+pub(crate) const INITIAL_LEXICAL_FORM_BUFFER_SIZE: usize = 4096;
+
 #[derive(Debug)]
 pub struct OpenedCursor<'a> {
     pub tx: Arc<Transaction>,
@@ -25,6 +31,8 @@ pub struct OpenedCursor<'a> {
     /// the arity (i.e., the number of columns) of the answers that the
     /// cursor computes.
     pub arity: usize,
+    /// The names of the answer variables, resolved on first use
+    headers: OnceCell<Vec<String>>,
 }
 
 impl<'a> OpenedCursor<'a> {
@@ -38,7 +46,7 @@ impl<'a> OpenedCursor<'a> {
         let c_cursor = cursor.inner;
         let multiplicity = Self::open(cursor.inner)?;
         let arity = Self::arity(c_cursor)?;
-        let opened_cursor = OpenedCursor { tx, cursor, arity };
+        let opened_cursor = OpenedCursor { tx, cursor, arity, headers: OnceCell::new() };
         Ok((opened_cursor, multiplicity))
     }
 
@@ -67,6 +75,138 @@ impl<'a> OpenedCursor<'a> {
         Ok(arity)
     }
 
+    /// Reposition the cursor at the first answer, so that the answers can be
+    /// read again without evaluating the statement again, and return the
+    /// multiplicity of the first row (zero if there are no answers).
+    ///
+    /// RDFox supports opening a cursor any number of times, which is also
+    /// why [`Cursor::consume`](Cursor::consume) can be called repeatedly on
+    /// the same `Cursor`.
+    pub fn reset(&mut self) -> Result<usize, ekg_error::Error> { Self::open(self.cursor.inner) }
+
+    /// Call `f` once for every distinct answer, from the first one, with the
+    /// cursor positioned on that answer and its multiplicity, i.e. the number
+    /// of times the answer occurs in the result (a bag, unless the query
+    /// uses `DISTINCT`).
+    ///
+    /// Duplicates are not expanded: an answer with multiplicity 3 results in
+    /// one call with 3, so that it is up to the caller whether to apply bag
+    /// or set semantics, without having to resolve the answer three times.
+    ///
+    /// Returns the total number of answers, counting duplicates.
+    pub fn for_each_row<F>(&mut self, mut f: F) -> Result<u64, ekg_error::Error>
+        where F: FnMut(&OpenedCursor, u64) {
+        let mut total = 0_u64;
+        let mut multiplicity = self.reset()?;
+        while multiplicity > 0 {
+            total += multiplicity as u64;
+            f(self, multiplicity as u64);
+            multiplicity = self.advance()?;
+        }
+        Ok(total)
+    }
+
+    /// Resolve all the columns of the current row into [`Term`]s in one
+    /// pass, `None` for unbound values.
+    ///
+    /// One scratch buffer is used for all the columns (and only grown when a
+    /// lexical form doesn't fit) which makes this much cheaper than resolving
+    /// the columns one by one for wide rows.
+    ///
+    /// Terms are not cached across rows: the cursor API that this crate uses
+    /// returns lexical forms per column rather than resource IDs, so there is
+    /// no key to cache them by.
+    pub fn resolve_row(&self) -> Result<Vec<Option<Term>>, ekg_error::Error> {
+        let mut buffer = vec![0u8; INITIAL_LEXICAL_FORM_BUFFER_SIZE];
+        (0..self.arity)
+            .map(|term_index| self.resolve_term(term_index, &mut buffer))
+            .collect()
+    }
+
+    /// Resolve the given column of the current row into a [`Term`], like
+    /// [`resolve_row`](Self::resolve_row) does for all columns, but when a
+    /// language is given return `None` for every literal that isn't tagged
+    /// with that language (compared case-insensitively), including literals
+    /// without a language tag. IRIs and blank nodes are always returned.
+    ///
+    /// This is meant for picking, say, the English `rdfs:label` out of many.
+    pub fn resolve_term_filtered(
+        &self,
+        term_index: usize,
+        language: Option<&str>,
+    ) -> Result<Option<Term>, ekg_error::Error> {
+        let mut buffer = vec![0u8; INITIAL_LEXICAL_FORM_BUFFER_SIZE];
+        let term = self.resolve_term(term_index, &mut buffer)?;
+        let (Some(language), Some(resolved)) = (language, term.as_ref()) else {
+            return Ok(term);
+        };
+        if !resolved.is_literal() ||
+            resolved
+                .language()
+                .is_some_and(|tag| tag.eq_ignore_ascii_case(language))
+        {
+            Ok(term)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn resolve_term(
+        &self,
+        term_index: usize,
+        buffer: &mut Vec<u8>,
+    ) -> Result<Option<Term>, ekg_error::Error> {
+        match self.lexical_form_into(term_index, buffer)? {
+            Some((datatype_id, lexical_form)) => Term::from_rdfox(datatype_id, lexical_form),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the lexical form and the datatype ID of the given column of the
+    /// current row into the given buffer, which is grown when RDFox needs
+    /// more room, or `None` if the variable is unbound in the current row.
+    pub(crate) fn lexical_form_into<'b>(
+        &self,
+        term_index: usize,
+        buffer: &'b mut Vec<u8>,
+    ) -> Result<Option<(u8, &'b str)>, ekg_error::Error> {
+        loop {
+            let mut lexical_form_size = 0_usize;
+            let mut datatype_id = DataType::UnboundValue as u8;
+            let mut resource_resolved = false;
+            database_call!(
+                "getting a resource value in lexical form",
+                CCursor_appendResourceLexicalForm(
+                    self.cursor.inner,
+                    term_index,
+                    buffer.as_mut_ptr() as *mut i8,
+                    buffer.len(),
+                    &mut lexical_form_size,
+                    &mut datatype_id as *mut u8,
+                    &mut resource_resolved,
+                )
+            )?;
+            if datatype_id == DataType::UnboundValue as u8 {
+                return Ok(None);
+            }
+            if !resource_resolved {
+                tracing::error!(
+                    target: LOG_TARGET_DATABASE,
+                    "Call to cursor for resource value in column #{term_index} could not be resolved"
+                );
+                return Err(ekg_error::Error::Unknown);
+            }
+            if lexical_form_size >= buffer.len() {
+                // RDFox tells us how much room it needed, try again with that
+                buffer.resize(lexical_form_size + 1, 0);
+                continue;
+            }
+            let lexical_form = std::str::from_utf8(&buffer[..lexical_form_size])?
+                .trim_end_matches('\0');
+            return Ok(Some((datatype_id, lexical_form)));
+        }
+    }
+
     /// TODO: Check why this panics when called after previous call returned
     /// zero
     pub fn advance(&mut self) -> Result<usize, ekg_error::Error> {
@@ -93,6 +233,20 @@ impl<'a> OpenedCursor<'a> {
         Transaction::begin_read_only(&self.cursor.connection)?.execute_and_rollback(|_tx| f(self))
     }
 
+    /// Return the names of the answer variables, in the order of the
+    /// columns, for instance for the header of a CSV file or a table.
+    ///
+    /// The names are only resolved the first time.
+    pub fn headers(&self) -> Result<Vec<String>, ekg_error::Error> {
+        if let Some(headers) = self.headers.get() {
+            return Ok(headers.clone());
+        }
+        let headers = (0..self.arity)
+            .map(|index| self.get_answer_variable_name(index))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.headers.get_or_init(|| headers).clone())
+    }
+
     /// Get the variable name used in the executed SPARQL statement representing
     /// the given column in the output.
     pub fn get_answer_variable_name(&self, index: usize) -> Result<String, ekg_error::Error> {
@@ -102,6 +256,6 @@ impl<'a> OpenedCursor<'a> {
             CCursor_getAnswerVariableName(self.cursor.inner, index, &mut c_buf)
         )?;
         let c_name = unsafe { std::ffi::CStr::from_ptr(c_buf) };
-        Ok(c_name.to_str().unwrap().to_owned())
+        Ok(c_name.to_str()?.to_owned())
     }
 }
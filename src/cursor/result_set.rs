@@ -0,0 +1,137 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {crate::Term, std::collections::HashMap};
+
+/// All answers of a query, resolved into [`Term`]s and held in memory, see
+/// [`Cursor::result_set`](crate::Cursor::result_set).
+///
+/// An answer that occurs more than once (its multiplicity) is repeated
+/// accordingly, so `rows` is the bag of answers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResultSet {
+    /// The names of the answer variables, without the leading `?`. Empty if
+    /// there are no answers since RDFox only reports them for an answer.
+    pub variables: Vec<String>,
+    /// The values of the answer variables, `None` where a variable is unbound
+    pub rows:      Vec<Vec<Option<Term>>>,
+}
+
+impl IntoIterator for ResultSet {
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+    type Item = Vec<Option<Term>>;
+
+    fn into_iter(self) -> Self::IntoIter { self.rows.into_iter() }
+}
+
+impl<'a> IntoIterator for &'a ResultSet {
+    type IntoIter = std::slice::Iter<'a, Vec<Option<Term>>>;
+    type Item = &'a Vec<Option<Term>>;
+
+    fn into_iter(self) -> Self::IntoIter { self.rows.iter() }
+}
+
+impl ResultSet {
+    pub fn len(&self) -> usize { self.rows.len() }
+
+    pub fn is_empty(&self) -> bool { self.rows.is_empty() }
+
+    /// Return the values of the given variable (with or without its leading
+    /// `?`), one for each row.
+    pub fn column(&self, name: &str) -> Result<Vec<Option<Term>>, ekg_error::Error> {
+        if self.rows.is_empty() {
+            return Ok(Vec::new());
+        }
+        let index = self.column_index(name)?;
+        Ok(self.rows.iter().map(|row| row[index].clone()).collect())
+    }
+
+    /// Collect the values of a single-column result as strings (see
+    /// [`Term::as_str`]), skipping the rows where the variable is unbound,
+    /// for instance to get all `?label` values.
+    pub fn to_strings(&self) -> Result<Vec<String>, ekg_error::Error> {
+        self.expect_columns(1)?;
+        Ok(self
+            .rows
+            .iter()
+            .filter_map(|row| row[0].as_ref().map(|term| term.as_str().to_string()))
+            .collect())
+    }
+
+    /// Collect a two-column result into a map from the values of the first
+    /// column to the values of the second one, as strings (see
+    /// [`Term::as_str`]), for instance to map `?id` to `?name`. Rows where
+    /// either variable is unbound are skipped, and for a key that occurs in
+    /// more than one row the last value wins.
+    pub fn to_string_map(&self) -> Result<HashMap<String, String>, ekg_error::Error> {
+        self.expect_columns(2)?;
+        Ok(self
+            .rows
+            .iter()
+            .filter_map(|row| {
+                match (&row[0], &row[1]) {
+                    (Some(key), Some(value)) => {
+                        Some((key.as_str().to_string(), value.as_str().to_string()))
+                    }
+                    _ => None,
+                }
+            })
+            .collect())
+    }
+
+    fn column_index(&self, name: &str) -> Result<usize, ekg_error::Error> {
+        let name = name.trim_start_matches(['?', '$']);
+        self.variables
+            .iter()
+            .position(|variable| variable == name)
+            .ok_or_else(|| {
+                ekg_error::Error::Exception {
+                    action:  "getting a column of a result set".to_string(),
+                    message: format!(
+                        "there is no variable ?{name}, only {:?}",
+                        self.variables
+                    ),
+                }
+            })
+    }
+
+    fn expect_columns(&self, number_of_columns: usize) -> Result<(), ekg_error::Error> {
+        if self.rows.is_empty() || self.variables.len() == number_of_columns {
+            return Ok(());
+        }
+        Err(ekg_error::Error::Exception {
+            action:  "collecting a result set".to_string(),
+            message: format!(
+                "expected {number_of_columns} column(s) but the result has {}: {:?}",
+                self.variables.len(),
+                self.variables
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::ResultSet, crate::Term};
+
+    #[test_log::test]
+    fn test_result_set() {
+        let result_set = ResultSet {
+            variables: vec!["id".to_string(), "name".to_string()],
+            rows:      vec![
+                vec![Some(Term::literal("1")), Some(Term::literal("one"))],
+                vec![Some(Term::literal("2")), None],
+            ],
+        };
+        assert_eq!(
+            result_set.column("?name").unwrap(),
+            vec![Some(Term::literal("one")), None]
+        );
+        assert!(result_set.column("unknown").is_err());
+        let map = result_set.to_string_map().unwrap();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("1").map(|name| name.as_str()), Some("one"));
+        assert!(result_set.to_strings().is_err());
+        assert_eq!(result_set.into_iter().count(), 2);
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    super::Cursor,
+    crate::{DataStoreConnection, Parameters, QueryType, Statement, Term, Transaction},
+    std::sync::Arc,
+};
+
+/// A triple produced by a CONSTRUCT query, see [`TripleCursor`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Triple {
+    pub subject:   Term,
+    pub predicate: Term,
+    pub object:    Term,
+    /// The graph, for a CONSTRUCT query whose template has a `GRAPH` block
+    pub graph:     Option<Term>,
+}
+
+/// A cursor over the triples of a CONSTRUCT query, resolved into
+/// [`Term`]s, so that they can be processed in memory rather than being
+/// serialized to (say) Turtle and parsed again.
+///
+/// RDFox answers a CONSTRUCT query with three columns, subject, predicate
+/// and object, or with four when the template puts the triples in a graph.
+#[derive(Debug)]
+pub struct TripleCursor {
+    cursor: Cursor,
+}
+
+impl TripleCursor {
+    pub fn create(
+        connection: &Arc<DataStoreConnection>,
+        parameters: &Parameters,
+        statement: &Statement,
+    ) -> Result<Self, ekg_error::Error> {
+        if statement.query_type()? != QueryType::Construct {
+            return Err(ekg_error::Error::Exception {
+                action:  "creating a triple cursor".to_string(),
+                message: "the statement is not a CONSTRUCT query".to_string(),
+            });
+        }
+        Ok(Self { cursor: Cursor::create(connection, parameters, statement)? })
+    }
+
+    /// Call `f` with every triple, at most `max_row` of them, and return the
+    /// number of triples. A triple that the template produces more than once
+    /// is passed only once.
+    pub fn consume<F>(
+        &mut self,
+        tx: &Arc<Transaction>,
+        max_row: usize,
+        mut f: F,
+    ) -> Result<usize, ekg_error::Error>
+        where F: FnMut(Triple) -> Result<(), ekg_error::Error> {
+        let mut count = 0_usize;
+        self.cursor.consume(tx, max_row, |row| {
+            f(triple_from_row(row.opened.resolve_row()?)?)?;
+            count += 1;
+            Ok::<(), ekg_error::Error>(())
+        })?;
+        Ok(count)
+    }
+
+    /// Read all triples into memory, at most `max_row` of them.
+    pub fn collect(
+        &mut self,
+        tx: &Arc<Transaction>,
+        max_row: usize,
+    ) -> Result<Vec<Triple>, ekg_error::Error> {
+        let mut triples = Vec::new();
+        self.consume(tx, max_row, |triple| {
+            triples.push(triple);
+            Ok(())
+        })?;
+        Ok(triples)
+    }
+
+    pub fn close(self) -> Result<(), ekg_error::Error> { self.cursor.close() }
+}
+
+fn triple_from_row(row: Vec<Option<Term>>) -> Result<Triple, ekg_error::Error> {
+    let arity = row.len();
+    let mut terms = row.into_iter();
+    let mut next = |position: &str| {
+        terms.next().flatten().ok_or_else(|| {
+            ekg_error::Error::Exception {
+                action:  "reading a triple from a cursor".to_string(),
+                message: format!("the {position} is unbound"),
+            }
+        })
+    };
+    match arity {
+        3 | 4 => {
+            Ok(Triple {
+                subject:   next("subject")?,
+                predicate: next("predicate")?,
+                object:    next("object")?,
+                graph:     if arity == 4 { Some(next("graph")?) } else { None },
+            })
+        }
+        _ => {
+            Err(ekg_error::Error::Exception {
+                action:  "reading a triple from a cursor".to_string(),
+                message: format!("expected three or four columns but got {arity}"),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Term;
+
+    #[test_log::test]
+    fn test_triple_from_row() {
+        let iri = Term::iri("https://example.com/x").unwrap();
+        let triple = super::triple_from_row(vec![
+            Some(iri.clone()),
+            Some(iri.clone()),
+            Some(Term::literal("x")),
+        ])
+        .unwrap();
+        assert_eq!(triple.object, Term::literal("x"));
+        assert_eq!(triple.graph, None);
+        assert!(super::triple_from_row(vec![Some(iri.clone()), None, Some(iri.clone())]).is_err());
+        assert!(super::triple_from_row(vec![Some(iri)]).is_err());
+    }
+}
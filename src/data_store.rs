@@ -1,22 +1,21 @@
 use {
     crate::{
-        connectable_data_store::ConnectableDataStore,
+        connectable_data_store::{ConnectableDataStore, ConnectionPool},
         Parameters,
         server_connection::ServerConnection,
     },
     owo_colors::OwoColorize,
-    r2d2::Pool
-    ,
     std::{
         fmt::{Display, Formatter},
         sync::Arc,
+        time::Duration,
     },
 };
 
 /// A `DataStore` encapsulates a unit of logically related information.
 ///
 /// See <https://docs.oxfordsemantic.tech/data-stores.html>
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Eq, PartialEq)]
 pub struct DataStore {
     pub name: String,
     pub parameters: Parameters,
@@ -48,7 +47,7 @@ impl DataStore {
         server_connection: &Arc<ServerConnection>,
         create: bool,
         release_on_return_to_pool: bool,
-    ) -> Result<Pool<ConnectableDataStore>, ekg_error::Error> {
+    ) -> Result<ConnectionPool, ekg_error::Error> {
         if create {
             server_connection.create_data_store(self)?;
         }
@@ -57,4 +56,17 @@ impl DataStore {
         let pool = cds.build_pool()?;
         Ok(pool)
     }
+
+    /// Same as [`DataStore::pool_for`] but with at most `max_size`
+    /// connections, waiting at most `connection_timeout` for a free
+    /// connection.
+    pub fn pool_with(
+        self: &Arc<DataStore>,
+        server_connection: &Arc<ServerConnection>,
+        max_size: u32,
+        connection_timeout: Duration,
+    ) -> Result<ConnectionPool, ekg_error::Error> {
+        ConnectableDataStore::new(self, server_connection, false)
+            .build_pool_with(max_size, connection_timeout)
+    }
 }
@@ -4,30 +4,60 @@
 use {
     colored::Colorize,
     crate::{
+        CancellationToken,
+        Cursor,
         database_call,
         DataStore,
+        exception::is_connection_lost,
+        exporter::Exporter,
+        ExportedGraph,
+        ExportManifest,
         FactDomain,
+        graph::{graph_from_iri, parse_graph},
+        GraphConnection,
+        GraphTarget,
+        ImportReport,
+        importer::{Importer, DEFAULT_READ_BUFFER_SIZE},
+        MaterializationReport,
         Namespaces,
         Parameters,
+        QueryPage,
+        QueryType,
         rdfox_api::{
             CDataStoreConnection,
             CDataStoreConnection_destroy,
             CDataStoreConnection_evaluateUpdate,
+            CDataStoreConnection_getDataStoreVersion,
             CDataStoreConnection_getName,
             CDataStoreConnection_getUniqueID,
             CDataStoreConnection_importAxiomsFromTriples,
             CDataStoreConnection_importDataFromFile,
+            CDataStoreConnection_requiresIncrementalReasoning,
+            CDataStoreConnection_rollbackTransaction,
+            CDataStoreConnection_updateMaterialization,
             CStatementResult,
             CUpdateType,
         },
+        APPLICATION_N_QUADS,
+        APPLICATION_N_TRIPLES,
+        APPLICATION_TRIG,
+        APPLICATION_X_DATALOG,
+        ResultSet,
         ServerConnection,
+        SPARQL_RESULTS_CSV,
+        SPARQL_RESULTS_TSV,
+        statistics::count_rules,
+        Statistics,
         Statement,
+        StatementTemplate,
         Streamer,
+        Term,
         Transaction,
+        Triple,
+        UpdateReport,
     },
     ekg_namespace::{
         consts::{
-            DEFAULT_BASE_IRI,
             DEFAULT_GRAPH_RDFOX,
             LOG_TARGET_DATABASE,
             LOG_TARGET_FILES,
@@ -44,18 +74,30 @@ use {
     std::{
         ffi::{CStr, CString},
         fmt::{Debug, Display, Formatter},
-        io::Write,
+        io::{Read, Write},
         mem::MaybeUninit,
         ops::Deref,
         os::unix::ffi::OsStrExt,
         path::Path,
         ptr::{self, null_mut},
-        sync::Arc,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+            Mutex,
+            Weak,
+        },
         time::Instant,
     },
 };
 
 /// A connection to a given [`DataStore`].
+///
+/// RDFox does not allow a connection to be used by more than one thread at a
+/// time. `DataStoreConnection` is `Send` and `Sync` so that it can be handed
+/// over to other threads (for instance by a
+/// [`ConnectionPool`](crate::ConnectionPool)), but it is up to the caller not
+/// to use it concurrently; wrap it in a [`SyncConnection`](crate::SyncConnection)
+/// to have that enforced with a lock.
 #[derive(Debug)]
 pub struct DataStoreConnection {
     pub data_store: Arc<DataStore>,
@@ -63,6 +105,15 @@ pub struct DataStoreConnection {
     pub(crate) inner: *mut CDataStoreConnection,
     started_at: Instant,
     pub number: usize,
+    /// The transaction that has most recently been started on this
+    /// connection, if it is still around.
+    pub(crate) current_transaction: Mutex<Weak<Transaction>>,
+    /// True from the moment a transaction has been begun on the C side
+    /// until it has been committed or rolled back successfully.
+    pub(crate) transaction_open: AtomicBool,
+    /// The prefixes that the [`RdfStore`](crate::RdfStore) methods evaluate
+    /// their statements with, see [`prefixes`](Self::prefixes).
+    prefixes: Mutex<Option<Arc<Namespaces>>>,
 }
 
 unsafe impl Sync for DataStoreConnection {}
@@ -90,6 +141,25 @@ impl Drop for DataStoreConnection {
         let duration = self.started_at.elapsed();
 
         let self_msg = format!("{self}");
+        // A `Transaction` keeps its connection alive, so a transaction can
+        // only still be open here if its commit or rollback failed
+        if self.transaction_open.load(Ordering::Relaxed) {
+            tracing::warn!(
+                target: LOG_TARGET_DATABASE,
+                conn = self.number,
+                "Rolling back the transaction that is still open on {self_msg}"
+            );
+            if let Err(err) = database_call!(
+                "rolling back the open transaction of a dropped connection",
+                CDataStoreConnection_rollbackTransaction(self.inner)
+            ) {
+                tracing::error!(
+                    target: LOG_TARGET_DATABASE,
+                    conn = self.number,
+                    "Could not roll back the open transaction on {self_msg}: {err}"
+                );
+            }
+        }
         unsafe {
             CDataStoreConnection_destroy(self.inner.cast());
         }
@@ -114,13 +184,196 @@ impl DataStoreConnection {
             inner,
             started_at: Instant::now(),
             number: Self::get_number(),
+            current_transaction: Mutex::new(Weak::new()),
+            transaction_open: AtomicBool::new(false),
+            prefixes: Mutex::new(None),
         }
     }
 
+    /// The prefixes of this connection, which the [`RdfStore`](crate::RdfStore)
+    /// methods evaluate their statements with. Unless other prefixes have
+    /// been set with [`set_prefixes`](Self::set_prefixes), these are empty
+    /// prefixes that are created once, on first use.
+    pub fn prefixes(&self) -> Result<Arc<Namespaces>, ekg_error::Error> {
+        let mut prefixes = self.prefixes.lock().unwrap();
+        if let Some(prefixes) = prefixes.as_ref() {
+            return Ok(prefixes.clone());
+        }
+        let empty = Namespaces::empty()?;
+        *prefixes = Some(empty.clone());
+        Ok(empty)
+    }
+
+    /// Set the prefixes that the [`RdfStore`](crate::RdfStore) methods
+    /// evaluate their statements with, so that these can use CURIEs without
+    /// declaring the prefixes themselves.
+    pub fn set_prefixes(&self, prefixes: &Arc<Namespaces>) {
+        *self.prefixes.lock().unwrap() = Some(prefixes.clone());
+    }
+
     pub fn same(self: &Arc<Self>, other: &Arc<Self>) -> bool { self.number == other.number }
 
+    /// Check the given statement, which can contain parameters written as
+    /// `?$name`, and return it as a template that can be executed with
+    /// different values for those parameters.
+    pub fn statement_template(
+        self: &Arc<Self>,
+        prefixes: &Arc<Namespaces>,
+        sparql: &str,
+    ) -> Result<StatementTemplate, ekg_error::Error> {
+        StatementTemplate::new(self, prefixes, sparql)
+    }
+
+    /// Evaluate the given query with the `query.explain` parameter switched
+    /// on and return the text that RDFox produced, which contains the query
+    /// plan and the statistics of its evaluation, to see why a query is slow.
+    ///
+    /// The text is meant for humans, its layout depends on the version of
+    /// RDFox. Note that the query really is evaluated.
+    pub fn explain(
+        self: &Arc<Self>,
+        prefixes: &Arc<Namespaces>,
+        sparql: &str,
+    ) -> Result<String, ekg_error::Error> {
+        let statement = Statement::new(prefixes, sparql.into())?;
+        let parameters = Parameters::empty()?
+            .fact_domain(FactDomain::ALL)?
+            .query_explain(true)?;
+        let mut buffer = Vec::new();
+        Streamer::run_with_parameters(
+            self,
+            &mut buffer,
+            &statement,
+            SPARQL_RESULTS_TSV.clone(),
+            None,
+            Some(&parameters),
+        )?;
+        Ok(String::from_utf8_lossy(buffer.as_slice()).into_owned())
+    }
+
+    /// Create a cursor for the given query that only matches the facts that
+    /// were asserted (imported or inserted), ignoring everything that the
+    /// rules derived, as with [`FactDomain::ASSERTED`] (`fact-domain=explicit`).
+    ///
+    /// Since RDFox maintains the derived facts incrementally, whether they
+    /// are up to date (see [`is_materialized`](Self::is_materialized)) makes no
+    /// difference to the answers. A fact that is both asserted and derived is
+    /// matched. Compare with a cursor over [`FactDomain::ALL`] to see what the
+    /// rules add.
+    pub fn query_asserted_only(
+        self: &Arc<Self>,
+        statement: &Statement,
+    ) -> Result<Cursor, ekg_error::Error> {
+        statement.cursor(
+            self,
+            &Parameters::empty()?.fact_domain(FactDomain::ASSERTED)?,
+        )
+    }
+
+    /// Return one page of the answers of the given query: at most `limit`
+    /// answers, starting at `offset`, for paged user interfaces.
+    ///
+    /// `OFFSET` and `LIMIT` are appended to the query (see
+    /// [`Statement::page`]), asking for one answer more than `limit` to find
+    /// out whether there is a next page. Without an `ORDER BY` the order of
+    /// the answers is not guaranteed to be the same for each page, so a
+    /// warning is logged for such a query.
+    pub fn query_page(
+        self: &Arc<Self>,
+        statement: &Statement,
+        parameters: &Parameters,
+        offset: u64,
+        limit: u64,
+    ) -> Result<QueryPage, ekg_error::Error> {
+        if !statement.has_order_by() {
+            tracing::warn!(
+                target: LOG_TARGET_DATABASE,
+                conn = self.number,
+                "Paging the answers of a query without ORDER BY, pages may overlap"
+            );
+        }
+        let mut cursor = statement
+            .page(offset, limit.saturating_add(1))?
+            .cursor(self, parameters)?;
+        let mut result_set = Transaction::begin_read_only(self)?
+            .execute_and_rollback(|ref tx| cursor.result_set(tx, usize::MAX))?;
+        let has_more = result_set.rows.len() as u64 > limit;
+        result_set.rows.truncate(limit as usize);
+        Ok(QueryPage { result_set, offset, has_more })
+    }
+
+    /// Establish a new connection to the same datastore, via a new
+    /// connection to the server with the same role, to replace this one when
+    /// it's no longer usable (see [`is_connection_lost`](crate::is_connection_lost)).
+    pub fn reconnect(&self) -> Result<Arc<DataStoreConnection>, ekg_error::Error> {
+        self.server_connection
+            .reconnect()?
+            .connect_to_data_store(&self.data_store)
+    }
+
+    /// Call `f` with this connection and, if that fails because the
+    /// connection was lost (see [`is_connection_lost`](crate::is_connection_lost)),
+    /// reconnect and call `f` once more with the new connection.
+    ///
+    /// Since `f` may be called twice, it should not have any side effects
+    /// outside of the datastore that would be wrong to repeat.
+    pub fn with_reconnect<T, F>(self: &Arc<Self>, f: F) -> Result<T, ekg_error::Error>
+        where F: Fn(&Arc<DataStoreConnection>) -> Result<T, ekg_error::Error> {
+        match f(self) {
+            Err(err) if is_connection_lost(&err) => {
+                tracing::warn!(
+                    target: LOG_TARGET_DATABASE,
+                    conn = self.number,
+                    "Lost {self}, reconnecting: {err}"
+                );
+                f(&self.reconnect()?)
+            }
+            result => result,
+        }
+    }
+
+    /// Return a token with which another thread can interrupt whatever is
+    /// running on this connection, such as a long-running query.
+    pub fn cancellation_token(self: &Arc<Self>) -> Arc<CancellationToken> {
+        CancellationToken::new(self)
+    }
+
+    /// Return the version of the datastore, which RDFox increments with
+    /// every committed change.
+    pub fn data_store_version(&self) -> Result<u64, ekg_error::Error> {
+        let mut version = 0_usize;
+        database_call!(
+            "getting the datastore version",
+            CDataStoreConnection_getDataStoreVersion(self.inner, &mut version)
+        )?;
+        Ok(version as u64)
+    }
+
+    /// Check that the connection and the server behind it are responsive,
+    /// for instance for a readiness probe. This only asks RDFox for the
+    /// version of the datastore: it creates no cursor and touches no data.
+    pub fn ping(&self) -> Result<(), ekg_error::Error> {
+        if self.inner.is_null() {
+            return Err(ekg_error::Error::Exception {
+                action:  "pinging a datastore connection".to_string(),
+                message: format!("{self} has been closed"),
+            });
+        }
+        self.data_store_version().map(|_| ())
+    }
+
+    /// Return the transaction that is currently active on this connection,
+    /// if any.
+    pub fn active_transaction(&self) -> Option<Arc<Transaction>> {
+        self.current_transaction
+            .lock()
+            .unwrap()
+            .upgrade()
+            .filter(|tx| tx.is_active())
+    }
+
     fn get_number() -> usize {
-        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::atomic::AtomicUsize;
         static COUNTER: AtomicUsize = AtomicUsize::new(1);
         COUNTER.fetch_add(1, Ordering::Relaxed)
     }
@@ -136,7 +389,7 @@ impl DataStoreConnection {
             CDataStoreConnection_getName(self.inner, &mut name)
         )?;
         let c_str = unsafe { CStr::from_ptr(name) };
-        Ok(c_str.to_str().unwrap().into())
+        Ok(c_str.to_str()?.into())
     }
 
     pub fn get_unique_id(&self) -> Result<String, ekg_error::Error> {
@@ -150,20 +403,37 @@ impl DataStoreConnection {
             CDataStoreConnection_getUniqueID(self.inner, &mut unique_id)
         )?;
         let c_str = unsafe { CStr::from_ptr(unique_id) };
-        Ok(c_str.to_str().unwrap().into())
+        Ok(c_str.to_str()?.into())
     }
 
-    /// Import RDF data from the given file into the given graph.
+    /// Import RDF data from the given file into the given graph, which can
+    /// be the default graph.
     ///
-    /// NOTE: Only supports turtle files at the moment.
-    pub fn import_data_from_file<P>(&self, file: P, graph: &Graph) -> Result<(), ekg_error::Error>
+    /// The format is derived from the extension of the file: `.ttl`, `.nt`,
+    /// `.nq`, `.trig` or `.rdf`. Any other extension results in an error.
+    /// With the `gzip` feature, these can be followed by `.gz` (as in
+    /// `dump.ttl.gz`) in which case the file is decompressed while it's
+    /// being imported.
+    ///
+    /// Unless a transaction is already active on this connection, RDFox runs
+    /// the import in its own read/write transaction that is committed when the
+    /// import succeeds and rolled back when it fails.
+    pub fn import_data_from_file<P>(
+        &self,
+        file: P,
+        graph: &GraphTarget,
+    ) -> Result<(), ekg_error::Error>
         where P: AsRef<Path> {
         assert!(
             !self.inner.is_null(),
             "invalid datastore connection"
         );
 
+        if file.as_ref().extension().is_some_and(|ext| ext == "gz") {
+            return self.import_gzip_file(file.as_ref(), graph);
+        }
         let rdf_file = file.as_ref().as_os_str().as_bytes();
+        let format = rdf_format_of(file.as_ref())?;
         tracing::trace!(
             target: LOG_TARGET_DATABASE,
             conn = self.number,
@@ -173,10 +443,21 @@ impl DataStoreConnection {
             self
         );
 
-        let c_graph_name = graph.as_c_string()?;
-        let file_name = CString::new(rdf_file).unwrap();
-        let format_name = CString::new(TEXT_TURTLE.as_ref()).unwrap();
+        let c_graph_name = graph.graph().as_c_string()?;
+        let file_name = CString::new(rdf_file)?;
+        let format_name = CString::new(format)?;
 
+        let span = tracing::debug_span!(
+            target: LOG_TARGET_DATABASE,
+            "import_file",
+            conn = self.number,
+            file = %file.as_ref().display(),
+            graph = %graph,
+            format,
+            elapsed_ms = tracing::field::Empty,
+        );
+        let _entered = span.enter();
+        let started_at = Instant::now();
         database_call!(
             format!("Importing data from {file_name:?} (format={format_name:?})").as_str(),
             CDataStoreConnection_importDataFromFile(
@@ -187,6 +468,7 @@ impl DataStoreConnection {
                 format_name.as_ptr() as *const std::os::raw::c_char,
             )
         )?;
+        span.record("elapsed_ms", started_at.elapsed().as_millis() as u64);
         tracing::debug!(
             target: LOG_TARGET_DATABASE,
             conn = self.number,
@@ -197,18 +479,477 @@ impl DataStoreConnection {
         Ok(())
     }
 
+    #[cfg(feature = "gzip")]
+    fn import_gzip_file(&self, file: &Path, graph: &GraphTarget) -> Result<(), ekg_error::Error> {
+        // The format is given by the extension before the `.gz`
+        let format = rdf_format_of(file.with_extension("").as_path())?;
+        let format = format.parse::<Mime>().map_err(|err| {
+            ekg_error::Error::Exception {
+                action:  format!("importing {}", file.display()),
+                message: err.to_string(),
+            }
+        })?;
+        let reader = flate2::read::GzDecoder::new(std::io::BufReader::new(
+            std::fs::File::open(file)?,
+        ));
+        self.import_data_from_reader(reader, &format, graph)
+            .map(|_| ())
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    fn import_gzip_file(&self, file: &Path, _graph: &GraphTarget) -> Result<(), ekg_error::Error> {
+        Err(ekg_error::Error::Exception {
+            action:  format!("importing {}", file.display()),
+            message: "importing .gz files requires the gzip feature".to_string(),
+        })
+    }
+
+    /// Import RDF data in the given format from the given reader into the
+    /// given graph, which can be the default graph.
+    ///
+    /// For dataset formats such as [`APPLICATION_TRIG`] and
+    /// [`APPLICATION_N_QUADS`] the given graph only receives the triples of
+    /// the default graph of the input, the triples in `GRAPH` blocks (or
+    /// with a fourth column) go into their own named graphs.
+    ///
+    /// The data is pulled from the reader in chunks while RDFox parses it so
+    /// the content is never buffered as a whole, which makes this suitable for
+    /// very large inputs.
+    ///
+    /// Returns the number of bytes that were read.
+    pub fn import_data_from_reader<R>(
+        &self,
+        reader: R,
+        format: &Mime,
+        graph: &GraphTarget,
+    ) -> Result<u64, ekg_error::Error>
+        where R: Read {
+        self.import_data_from_reader_with_buffer_size(
+            reader,
+            format,
+            graph,
+            DEFAULT_READ_BUFFER_SIZE,
+        )
+    }
+
+    /// Same as [`import_data_from_reader`](Self::import_data_from_reader) but
+    /// reading from the reader through a buffer of the given size rather
+    /// than [`DEFAULT_READ_BUFFER_SIZE`], for instance a larger one for a
+    /// fast local disk or a smaller one for a slow network source.
+    pub fn import_data_from_reader_with_buffer_size<R>(
+        &self,
+        reader: R,
+        format: &Mime,
+        graph: &GraphTarget,
+        read_buffer_size: usize,
+    ) -> Result<u64, ekg_error::Error>
+        where R: Read {
+        tracing::trace!(
+            target: LOG_TARGET_DATABASE,
+            conn = self.number,
+            "Importing {format} data from a reader into {:} of {:}",
+            graph,
+            self
+        );
+        Importer::run_with_progress(
+            self,
+            reader,
+            format,
+            graph,
+            CUpdateType::UPDATE_TYPE_ADDITION,
+            read_buffer_size,
+            None,
+        )
+    }
+
+    /// Same as [`import_data_from_reader`](Self::import_data_from_reader) but
+    /// returning an [`ImportReport`] with the number of bytes and lines that
+    /// were read, for imports that have to be auditable.
+    ///
+    /// RDFox does not report the number of facts that an import added, see
+    /// [`ImportReport`]. Counting the facts before and after would double the
+    /// cost of a large import and be wrong as soon as anything else commits
+    /// on the datastore at the same time, so that's left to the caller.
+    pub fn import_data_with_report<R>(
+        &self,
+        reader: R,
+        format: &Mime,
+        graph: &GraphTarget,
+    ) -> Result<ImportReport, ekg_error::Error>
+        where R: Read {
+        let mut report = ImportReport::default();
+        self.import_data_with_progress(reader, format, graph, |bytes_read, lines_read| {
+            report = ImportReport { bytes_read, lines_read };
+        })?;
+        tracing::debug!(
+            target: LOG_TARGET_DATABASE,
+            conn = self.number,
+            "Imported {format} data: {report}"
+        );
+        Ok(report)
+    }
+
+    /// Same as [`import_data_from_reader`](Self::import_data_from_reader) but
+    /// calling `progress` with the number of bytes read so far and the
+    /// number of lines read so far, after every chunk of data that RDFox
+    /// pulls in, for instance to drive a progress bar.
+    ///
+    /// RDFox does not report how many triples it has parsed while the import
+    /// runs, so the second number counts lines, which is the number of
+    /// triples (or quads) for N-Triples and N-Quads and an approximation for
+    /// other formats. A panic in `progress` aborts the import with an error.
+    pub fn import_data_with_progress<R, F>(
+        &self,
+        reader: R,
+        format: &Mime,
+        graph: &GraphTarget,
+        mut progress: F,
+    ) -> Result<u64, ekg_error::Error>
+        where
+            R: Read,
+            F: FnMut(u64, u64),
+    {
+        Importer::run_with_progress(
+            self,
+            reader,
+            format,
+            graph,
+            CUpdateType::UPDATE_TYPE_ADDITION,
+            DEFAULT_READ_BUFFER_SIZE,
+            Some(&mut progress),
+        )
+    }
+
+    /// Add the given triples to the given graph, which can be the default
+    /// graph, committing a read/write transaction for every
+    /// `chunk_size` triples so that memory use stays bounded however many
+    /// triples there are.
+    ///
+    /// Returns the number of committed chunks. When a chunk fails, the chunks
+    /// before it stay committed and the error says how many there were, so
+    /// that the caller can resume by skipping `chunks * chunk_size` triples.
+    pub fn insert_batched<I>(
+        self: &Arc<Self>,
+        triples: I,
+        chunk_size: usize,
+        graph: &GraphTarget,
+    ) -> Result<usize, ekg_error::Error>
+        where I: IntoIterator<Item = (Term, Term, Term)> {
+        if chunk_size == 0 {
+            return Err(ekg_error::Error::Exception {
+                action:  format!("inserting triples into {graph}"),
+                message: "the chunk size must be at least one".to_string(),
+            });
+        }
+        let graph_connection = GraphConnection::new(self.clone(), graph.clone(), None);
+        let mut triples = triples.into_iter().peekable();
+        let mut chunks = 0_usize;
+        while triples.peek().is_some() {
+            let chunk = triples.by_ref().take(chunk_size).collect::<Vec<_>>();
+            Transaction::begin_read_write(self)
+                .and_then(|tx| {
+                    tx.update_and_commit(|tx| graph_connection.insert_all(&tx, chunk.as_slice()))
+                })
+                .map_err(|err| {
+                    ekg_error::Error::Exception {
+                        action:  format!("inserting triples into {}", graph_connection.graph),
+                        message: format!(
+                            "chunk {} failed after {chunks} chunks ({} triples) were committed: \
+                             {err}",
+                            chunks + 1,
+                            chunks * chunk_size
+                        ),
+                    }
+                })?;
+            chunks += 1;
+            tracing::trace!(
+                target: LOG_TARGET_DATABASE,
+                conn = self.number,
+                "Committed chunk {chunks} of {} triples",
+                chunk.len()
+            );
+        }
+        Ok(chunks)
+    }
+
+    /// Apply a changeset to the datastore atomically: first remove all the
+    /// `deletes`, then add all the `adds`, in one read/write transaction
+    /// that is rolled back entirely if any of it fails.
+    ///
+    /// Each triple goes into the graph in its own `graph` field if it has
+    /// one, otherwise into the given graph.
+    ///
+    /// Returns the net change of the number of asserted triples, which can
+    /// be negative. The triples are counted at the start and at the end of
+    /// the transaction, so concurrent writers don't affect the figure.
+    pub fn apply_changeset(
+        self: &Arc<Self>,
+        adds: &[Triple],
+        deletes: &[Triple],
+        graph: &GraphTarget,
+    ) -> Result<i64, ekg_error::Error> {
+        let deletes = triples_by_graph(deletes, graph)?;
+        let adds = triples_by_graph(adds, graph)?;
+        let net_change = Transaction::begin_read_write(self)?.update_and_commit(|tx| {
+            let triples_before = self.get_triples_count(&tx, FactDomain::ASSERTED)?;
+            for (graph, triples) in deletes.iter() {
+                GraphConnection::new(self.clone(), graph.clone(), None)
+                    .remove_all(&tx, triples.as_slice())?;
+            }
+            for (graph, triples) in adds.iter() {
+                GraphConnection::new(self.clone(), graph.clone(), None)
+                    .insert_all(&tx, triples.as_slice())?;
+            }
+            let triples_after = self.get_triples_count(&tx, FactDomain::ASSERTED)?;
+            Ok::<i64, ekg_error::Error>(triples_after as i64 - triples_before as i64)
+        })?;
+        tracing::debug!(
+            target: LOG_TARGET_DATABASE,
+            conn = self.number,
+            "Applied a changeset of {} deletions and {} additions, net change {net_change}",
+            deletes.iter().map(|(_, triples)| triples.len()).sum::<usize>(),
+            adds.iter().map(|(_, triples)| triples.len()).sum::<usize>(),
+        );
+        Ok(net_change)
+    }
+
+    /// Add the given Datalog rules, in RDFox's Datalog syntax, to the
+    /// datastore. The given namespaces are declared in front of the rules
+    /// so that they can be used in them.
+    ///
+    /// RDFox incrementally materializes the consequences of the new rules
+    /// when the surrounding transaction is committed.
+    pub fn add_rules(&self, prefixes: &Namespaces, rules: &str) -> Result<(), ekg_error::Error> {
+        self.import_rules(prefixes, rules, CUpdateType::UPDATE_TYPE_ADDITION)
+    }
+
+    /// Remove the given Datalog rules, which have to be written exactly as
+    /// they were added, from the datastore, which also retracts all the
+    /// facts that could only be derived with them.
+    pub fn remove_rules(&self, prefixes: &Namespaces, rules: &str) -> Result<(), ekg_error::Error> {
+        self.import_rules(prefixes, rules, CUpdateType::UPDATE_TYPE_DELETION)
+    }
+
+    fn import_rules(
+        &self,
+        prefixes: &Namespaces,
+        rules: &str,
+        update_type: CUpdateType,
+    ) -> Result<(), ekg_error::Error> {
+        let content = format!("{prefixes}\n{rules}");
+        Importer::run(
+            self,
+            content.as_bytes(),
+            APPLICATION_X_DATALOG.deref(),
+            &GraphTarget::Default,
+            update_type,
+        )
+        .map(|_| ())
+    }
+
+    /// Bring the inferred facts up to date with the current data and rules
+    /// (incremental reasoning), in its own read/write transaction, so that
+    /// the cost of reasoning is paid at a moment of the caller's choosing,
+    /// for instance after a bulk import.
+    ///
+    /// The facts are not counted, so the report has no
+    /// [`facts_added`](MaterializationReport::facts_added), see
+    /// [`materialize_and_count`](Self::materialize_and_count).
+    pub fn materialize(self: &Arc<Self>) -> Result<MaterializationReport, ekg_error::Error> {
+        self.materialize_counting(false)
+    }
+
+    /// Same as [`materialize`](Self::materialize) but also reporting how
+    /// many facts were added, at the cost of counting all facts at the start
+    /// and at the end of the transaction.
+    pub fn materialize_and_count(
+        self: &Arc<Self>,
+    ) -> Result<MaterializationReport, ekg_error::Error> {
+        self.materialize_counting(true)
+    }
+
+    fn materialize_counting(
+        self: &Arc<Self>,
+        count: bool,
+    ) -> Result<MaterializationReport, ekg_error::Error> {
+        let started_at = Instant::now();
+        let facts_added = Transaction::begin_read_write(self)?.update_and_commit(|tx| {
+            let facts_before = if count {
+                Some(self.get_triples_count(&tx, FactDomain::ALL)?)
+            } else {
+                None
+            };
+            database_call!(
+                "updating the materialization",
+                CDataStoreConnection_updateMaterialization(self.inner)
+            )?;
+            match facts_before {
+                Some(facts_before) => {
+                    let facts_after = self.get_triples_count(&tx, FactDomain::ALL)?;
+                    Ok::<_, ekg_error::Error>(Some(facts_after as i64 - facts_before as i64))
+                }
+                None => Ok(None),
+            }
+        })?;
+        let report = MaterializationReport { facts_added, duration: started_at.elapsed() };
+        tracing::info!(
+            target: LOG_TARGET_DATABASE,
+            conn = self.number,
+            "Materialized {}: {report}",
+            self.data_store
+        );
+        Ok(report)
+    }
+
+    /// Return true if the inferred facts are up to date with the current
+    /// data and rules, i.e. if [`materialize`](Self::materialize) would have
+    /// nothing to do.
+    pub fn is_materialized(&self) -> Result<bool, ekg_error::Error> {
+        let mut requires_reasoning = false;
+        database_call!(
+            "checking whether incremental reasoning is required",
+            CDataStoreConnection_requiresIncrementalReasoning(self.inner, &mut requires_reasoning)
+        )?;
+        Ok(!requires_reasoning)
+    }
+
+    /// Return all the rules in the datastore, in RDFox's Datalog syntax.
+    pub fn list_rules(&self) -> Result<String, ekg_error::Error> {
+        let mut buffer = Vec::new();
+        Exporter::run(
+            self,
+            &mut buffer,
+            APPLICATION_X_DATALOG.deref(),
+            &Parameters::empty()?,
+        )?;
+        Ok(String::from_utf8_lossy(buffer.as_slice()).into_owned())
+    }
+
+    /// Export all facts in the datastore to the given writer in TriG format
+    /// and return the number of bytes that were written.
+    ///
+    /// The triples of the default graph are written at the top level and
+    /// those of every named graph in their own `GRAPH` block, so that
+    /// importing the output again with
+    /// [`import_data_from_reader`](Self::import_data_from_reader) and
+    /// [`APPLICATION_TRIG`] restores the same dataset. This uses RDFox's
+    /// export rather than a CONSTRUCT query, since the latter can only
+    /// produce triples and would merge all graphs into one.
+    pub fn export_to_trig<W: Write>(&self, writer: W) -> Result<u64, ekg_error::Error> {
+        Exporter::run(
+            self,
+            writer,
+            APPLICATION_TRIG.deref(),
+            &Parameters::empty()?,
+        )
+    }
+
+    /// Write every graph in the datastore, the default graph as well as all
+    /// named graphs, to a file of its own in the given directory, in the given
+    /// triple format (Turtle, N-Triples or RDF/XML), plus a manifest
+    /// ([`EXPORT_MANIFEST_FILE_NAME`](crate::EXPORT_MANIFEST_FILE_NAME)) that
+    /// lists the files with their graph, format and number of triples.
+    ///
+    /// The directory is created if it doesn't exist, files that are already
+    /// in it are overwritten. Use [`import_all`](Self::import_all) to restore.
+    ///
+    /// The C API of RDFox has no binary dump and restore of a datastore, so
+    /// there is no faster alternative that skips serializing and parsing.
+    /// To avoid re-importing a large store on every start, let RDFox persist
+    /// it instead, see
+    /// [`Parameters::persist_to_directory`](crate::Parameters::persist_to_directory).
+    pub fn export_all(
+        self: &Arc<Self>,
+        dir: &Path,
+        format: &Mime,
+    ) -> Result<ExportManifest, ekg_error::Error> {
+        let extension = rdf_extension_of(format)?;
+        std::fs::create_dir_all(dir)?;
+        let mut manifest = ExportManifest { format: format.to_string(), graphs: Vec::new() };
+        for (index, graph) in self.list_graphs()?.into_iter().enumerate() {
+            let graph_connection = GraphConnection::new(self.clone(), graph.into(), None);
+            let (name, file) = if graph_connection.is_default_graph() {
+                ("default".to_string(), format!("default.{extension}"))
+            } else {
+                (
+                    graph_connection
+                        .graph
+                        .graph()
+                        .as_display_iri()
+                        .to_string()
+                        .trim_start_matches('<')
+                        .trim_end_matches('>')
+                        .to_string(),
+                    format!("graph-{index:04}.{extension}"),
+                )
+            };
+            let writer = std::io::BufWriter::new(std::fs::File::create(dir.join(&file))?);
+            let triples = graph_connection.export(writer, format)?;
+            tracing::debug!(
+                target: LOG_TARGET_DATABASE,
+                conn = self.number,
+                "Exported {triples} triples of {name} to {file}"
+            );
+            manifest.graphs.push(ExportedGraph { graph: name, file, triples });
+        }
+        manifest.write_to_directory(dir)?;
+        Ok(manifest)
+    }
+
+    /// Import all files listed in the manifest in the given directory, as
+    /// written by [`export_all`](Self::export_all), each into its own graph.
+    ///
+    /// Afterwards every graph has to contain at least as many triples as the
+    /// manifest says were exported, otherwise an error is returned.
+    pub fn import_all(self: &Arc<Self>, dir: &Path) -> Result<ExportManifest, ekg_error::Error> {
+        let manifest = ExportManifest::read_from_directory(dir)?;
+        for exported in manifest.graphs.iter() {
+            let graph = GraphTarget::from(parse_graph(exported.graph.as_str())?);
+            self.import_data_from_file(dir.join(&exported.file), &graph)?;
+            let graph_connection = GraphConnection::new(self.clone(), graph, None);
+            let triples = graph_connection.count_triples(FactDomain::ASSERTED)? as u64;
+            if triples < exported.triples {
+                return Err(ekg_error::Error::Exception {
+                    action:  format!("importing {}", dir.join(&exported.file).display()),
+                    message: format!(
+                        "graph {} has {triples} triples, expected at least {}",
+                        exported.graph, exported.triples
+                    ),
+                });
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Import the given Turtle content into the given graph, which can be
+    /// the default graph.
+    ///
+    /// The given namespaces are declared (as SPARQL-style `PREFIX` lines,
+    /// which Turtle accepts as well) in front of the content so that the
+    /// snippet can use them.
+    pub fn import_turtle_str(
+        &self,
+        prefixes: &Namespaces,
+        turtle: &str,
+        graph: &GraphTarget,
+    ) -> Result<(), ekg_error::Error> {
+        let content = format!("{prefixes}\n{turtle}");
+        self.import_data_from_reader(content.as_bytes(), TEXT_TURTLE.deref(), graph)
+            .map(|_| ())
+    }
+
     pub fn import_axioms_from_triples(
         &self,
-        source_graph: &Graph,
-        target_graph: &Graph,
+        source_graph: &GraphTarget,
+        target_graph: &GraphTarget,
     ) -> Result<(), ekg_error::Error> {
         assert!(
             !self.inner.is_null(),
             "invalid datastore connection"
         );
 
-        let c_source_graph_name = source_graph.as_c_string()?;
-        let c_target_graph_name = target_graph.as_c_string()?;
+        let c_source_graph_name = source_graph.graph().as_c_string()?;
+        let c_target_graph_name = target_graph.graph().as_c_string()?;
 
         database_call!(
             "importing axioms",
@@ -223,7 +964,7 @@ impl DataStoreConnection {
         tracing::debug!(
             target: LOG_TARGET_DATABASE,
             conn = self.number,
-            "Imported axioms from {:} into graph {:}",
+            "Imported axioms from {:} into {:}",
             source_graph,
             target_graph
         );
@@ -242,7 +983,7 @@ impl DataStoreConnection {
     pub fn import_rdf_from_directory(
         &self,
         root: &Path,
-        graph: &Graph,
+        graph: &GraphTarget,
     ) -> Result<u16, ekg_error::Error> {
         let mut count = 0u16;
         let regex = Regex::new(r"^.*.ttl$").unwrap();
@@ -330,59 +1071,602 @@ impl DataStoreConnection {
         Ok(statement_result)
     }
 
+    /// Evaluate the given SPARQL update (`INSERT`, `DELETE`, etc.), using the
+    /// given prefixes, in a read/write transaction.
+    ///
+    /// The update is atomic: the transaction is committed when the update
+    /// succeeds and rolled back entirely when it fails. Returns the number of
+    /// inserted and deleted facts as reported by RDFox.
+    pub fn update(
+        self: &Arc<Self>,
+        prefixes: &Arc<Namespaces>,
+        sparql: &str,
+    ) -> Result<UpdateReport, ekg_error::Error> {
+        let statement = Statement::new(prefixes, sparql.into())?;
+        let query_type = statement.query_type()?;
+        if query_type != QueryType::Update {
+            return Err(ekg_error::Error::Exception {
+                action:  "evaluating an update".to_string(),
+                message: format!("expected an update but got a {query_type:?} statement"),
+            });
+        }
+        let report: UpdateReport = Transaction::begin_read_write(self)?
+            .update_and_commit(|_tx| self.evaluate_update(&statement, &Parameters::empty()?))?
+            .into();
+        tracing::debug!(
+            target: LOG_TARGET_DATABASE,
+            conn = self.number,
+            "Evaluated update: {report}"
+        );
+        Ok(report)
+    }
+
+    /// Evaluate the given ASK query in a read-only transaction and return its
+    /// answer.
+    ///
+    /// Returns an error if the statement is not an ASK query.
+    pub fn evaluate_ask(
+        self: &Arc<Self>,
+        statement: &Statement,
+        fact_domain: FactDomain,
+    ) -> Result<bool, ekg_error::Error> {
+        let query_type = statement.query_type()?;
+        if query_type != QueryType::Ask {
+            return Err(ekg_error::Error::Exception {
+                action:  "evaluating an ASK query".to_string(),
+                message: format!("expected an ASK query but got a {query_type:?} statement"),
+            });
+        }
+        // RDFox answers an ASK query with a single empty row if it's true and
+        // with no rows at all if it's false
+        let mut cursor = statement.cursor(
+            self,
+            &Parameters::empty()?.fact_domain(fact_domain)?,
+        )?;
+        let count = Transaction::begin_read_only(self)?
+            .execute_and_rollback(|ref tx| cursor.count(tx))?;
+        Ok(count > 0)
+    }
+
+    /// Evaluate the given ASK query text over all facts, asserted and
+    /// derived, and return its answer, for quick existence checks.
+    ///
+    /// Returns an error if the text is not an ASK query, see
+    /// [`evaluate_ask`](Self::evaluate_ask).
+    pub fn ask(
+        self: &Arc<Self>,
+        prefixes: &Arc<Namespaces>,
+        sparql: &str,
+    ) -> Result<bool, ekg_error::Error> {
+        self.evaluate_ask(
+            &Statement::new(prefixes, sparql.into())?,
+            FactDomain::ALL,
+        )
+    }
+
+    /// Evaluate the given SELECT query text over all facts, asserted and
+    /// derived, in a read-only transaction and return all its answers.
+    pub fn select(
+        self: &Arc<Self>,
+        prefixes: &Arc<Namespaces>,
+        sparql: &str,
+    ) -> Result<ResultSet, ekg_error::Error> {
+        let statement = Statement::new(prefixes, sparql.into())?;
+        let mut cursor = statement.cursor(
+            self,
+            &Parameters::empty()?.fact_domain(FactDomain::ALL)?,
+        )?;
+        Transaction::begin_read_only(self)?
+            .execute_and_rollback(|ref tx| cursor.result_set(tx, usize::MAX))
+    }
+
+    /// Delete all facts, in the default graph and in all named graphs, from
+    /// the datastore in one read/write transaction.
+    ///
+    /// Only facts are removed: rules, axioms and declared prefixes survive a
+    /// clear, so facts that rules derive without depending on any of the
+    /// deleted facts will still be there afterwards.
+    pub fn clear(self: &Arc<Self>) -> Result<(), ekg_error::Error> {
+        let statement = Statement::new(
+            &Namespaces::empty()?,
+            formatdoc!(
+                r##"
+                DELETE {{ ?s ?p ?o }} WHERE {{ ?s ?p ?o }} ;
+                DELETE {{ GRAPH ?g {{ ?s ?p ?o }} }} WHERE {{ GRAPH ?g {{ ?s ?p ?o }} }}
+                "##
+            )
+                .into(),
+        )?;
+        Transaction::begin_read_write(self)?.update_and_commit(|_tx| {
+            self.evaluate_update(&statement, &Parameters::empty()?)
+                .map(|_| ())
+        })?;
+        tracing::debug!(
+            target: LOG_TARGET_DATABASE,
+            conn = self.number,
+            "Cleared {:}",
+            self.data_store
+        );
+        Ok(())
+    }
+
+    /// Delete all facts from the given graph, which can be the default
+    /// graph, in one read/write transaction, leaving all other graphs
+    /// untouched.
+    ///
+    /// As with [`clear`](Self::clear), rules, axioms and prefixes are kept.
+    pub fn clear_graph(self: &Arc<Self>, graph: &GraphTarget) -> Result<(), ekg_error::Error> {
+        let pattern = match graph {
+            GraphTarget::Default => "?s ?p ?o".to_string(),
+            GraphTarget::Named(graph) => format!("GRAPH {} {{ ?s ?p ?o }}", graph.as_display_iri()),
+        };
+        let statement = Statement::new(
+            &Namespaces::empty()?,
+            format!("DELETE {{ {pattern} }} WHERE {{ {pattern} }}").into(),
+        )?;
+        Transaction::begin_read_write(self)?.update_and_commit(|_tx| {
+            self.evaluate_update(&statement, &Parameters::empty()?)
+                .map(|_| ())
+        })?;
+        tracing::debug!(
+            target: LOG_TARGET_DATABASE,
+            conn = self.number,
+            "Cleared {:} of {:}",
+            graph,
+            self.data_store
+        );
+        Ok(())
+    }
+
+    /// Delete all triples that match the given pattern, where `None` matches
+    /// anything, from the given graph, in one read/write transaction.
+    ///
+    /// This is evaluated as a single `DELETE WHERE` by RDFox, so the matches
+    /// are never collected on the Rust side.
+    ///
+    /// Blank nodes are rejected: in a `DELETE WHERE` pattern a blank node acts
+    /// as a variable rather than as the node itself, so it would delete far
+    /// more than the given pattern.
+    pub fn delete_pattern(
+        self: &Arc<Self>,
+        subject: Option<&Term>,
+        predicate: Option<&Term>,
+        object: Option<&Term>,
+        graph: &GraphTarget,
+    ) -> Result<UpdateReport, ekg_error::Error> {
+        if subject.is_some_and(|subject| subject.is_literal()) ||
+            predicate.is_some_and(|predicate| !predicate.is_iri())
+        {
+            return Err(ekg_error::Error::Exception {
+                action:  "deleting triples".to_string(),
+                message: "the subject cannot be a literal and the predicate has to be an IRI"
+                    .to_string(),
+            });
+        }
+        if [subject, predicate, object]
+            .iter()
+            .flatten()
+            .any(|term| term.is_blank_node())
+        {
+            return Err(ekg_error::Error::Exception {
+                action:  "deleting triples".to_string(),
+                message: "a blank node would match any node, use None for that".to_string(),
+            });
+        }
+        let term_or = |term: Option<&Term>, variable: &str| {
+            term.map(|term| term.to_string())
+                .unwrap_or_else(|| variable.to_string())
+        };
+        let pattern = format!(
+            "{} {} {}",
+            term_or(subject, "?s"),
+            term_or(predicate, "?p"),
+            term_or(object, "?o")
+        );
+        let sparql = match graph {
+            GraphTarget::Named(graph) => {
+                format!(
+                    "DELETE WHERE {{ GRAPH {} {{ {pattern} }} }}",
+                    graph.as_display_iri()
+                )
+            }
+            GraphTarget::Default => format!("DELETE WHERE {{ {pattern} }}"),
+        };
+        let statement = Statement::new(&Namespaces::empty()?, sparql.into())?;
+        let report = Transaction::begin_read_write(self)?.update_and_commit(|_tx| {
+            self.evaluate_update(&statement, &Parameters::empty()?)
+                .map(UpdateReport::from)
+        })?;
+        tracing::debug!(
+            target: LOG_TARGET_DATABASE,
+            conn = self.number,
+            "Deleted triples matching {pattern}: {report}"
+        );
+        Ok(report)
+    }
+
+    /// Evaluate the given statement and stream the results, serialized in
+    /// the given format, to the given writer.
+    ///
+    /// The base IRI is optional, pass `None` when it's not meaningful, such
+    /// as for the answers of a SELECT query in CSV.
+    ///
+    /// The statement is evaluated over all facts ([`FactDomain::ALL`]), with
+    /// the given parameters, if any, on top of that. They can override the
+    /// fact domain and set any other per-statement option of RDFox, such as
+    /// `query.explain` (see [`Parameters::query_explain`]).
     pub fn evaluate_to_stream<'a, W>(
         self: &Arc<Self>,
         writer: W,
         statement: &'a Statement,
-        mime_type: &'static Mime,
+        mime_type: &Mime,
         base_iri: Option<&Iri>,
+        parameters: Option<&'a Parameters>,
     ) -> Result<Streamer<'a, W>, ekg_error::Error>
         where
             W: 'a + Write,
     {
-        Streamer::run(
+        Streamer::run_with_parameters(
             self,
             writer,
             statement,
+            mime_type.clone(),
+            base_namespace(base_iri)?,
+            parameters,
+        )
+    }
+
+    /// Same as [`evaluate_to_stream`](Self::evaluate_to_stream) but
+    /// compressing the output with gzip on the fly.
+    ///
+    /// Returns the number of solutions.
+    #[cfg(feature = "gzip")]
+    pub fn evaluate_to_gzip_stream<W>(
+        self: &Arc<Self>,
+        writer: W,
+        statement: &Statement,
+        mime_type: &Mime,
+        base_iri: Option<&Iri>,
+        parameters: Option<&Parameters>,
+    ) -> Result<u64, ekg_error::Error>
+        where
+            W: Write,
+    {
+        let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+        let number_of_solutions = self
+            .evaluate_to_stream(
+                &mut encoder,
+                statement,
+                mime_type,
+                base_iri,
+                parameters,
+            )?
+            .number_of_solutions();
+        // Write the gzip trailer, without it the output is truncated
+        encoder.finish()?;
+        Ok(number_of_solutions)
+    }
+
+    /// Same as [`evaluate_to_stream`](Self::evaluate_to_stream) (which uses
+    /// [`FactDomain::ALL`]) but only matching the facts in the given fact
+    /// domain, for instance to see only what the reasoner derived.
+    pub fn evaluate_to_stream_in_fact_domain<'a, W>(
+        self: &Arc<Self>,
+        writer: W,
+        statement: &'a Statement,
+        mime_type: &Mime,
+        base_iri: Option<&Iri>,
+        fact_domain: FactDomain,
+    ) -> Result<Streamer<'a, W>, ekg_error::Error>
+        where
+            W: 'a + Write,
+    {
+        Streamer::run_in_fact_domain(
+            self,
+            writer,
+            statement,
+            mime_type.clone(),
+            base_namespace(base_iri)?,
+            fact_domain,
+        )
+    }
+
+    /// Same as [`evaluate_to_stream`](Self::evaluate_to_stream) but
+    /// collecting the serialized results in a `String`, for small results
+    /// such as in tests.
+    ///
+    /// Returns an error if the results are not valid UTF-8.
+    pub fn evaluate_to_string(
+        self: &Arc<Self>,
+        statement: &Statement,
+        mime_type: &Mime,
+        parameters: Option<&Parameters>,
+    ) -> Result<String, ekg_error::Error> {
+        let mut buffer = Vec::new();
+        self.evaluate_to_stream(
+            &mut buffer,
+            statement,
             mime_type,
-            Namespace::declare_from_str(
-                "base",
-                base_iri
-                    .as_ref()
-                    .map(|iri| iri.as_str())
-                    .unwrap_or_else(|| DEFAULT_BASE_IRI),
-            )?,
+            None,
+            parameters,
+        )?;
+        String::from_utf8(buffer).map_err(|err| {
+            ekg_error::Error::Exception {
+                action:  format!("evaluating a statement to {mime_type}"),
+                message: format!("the results are not valid UTF-8: {err}"),
+            }
+        })
+    }
+
+    /// Evaluate the given CONSTRUCT statement and stream the resulting
+    /// triples to the given writer in N-Triples format.
+    pub fn evaluate_to_n_triples<'a, W>(
+        self: &Arc<Self>,
+        writer: W,
+        statement: &'a Statement,
+    ) -> Result<Streamer<'a, W>, ekg_error::Error>
+        where
+            W: 'a + Write,
+    {
+        self.evaluate_to_stream(
+            writer,
+            statement,
+            APPLICATION_N_TRIPLES.deref(),
+            None,
+            None,
+        )
+    }
+
+    /// Evaluate the given statement and stream the resulting quads to the
+    /// given writer in N-Quads format.
+    ///
+    /// A CONSTRUCT query can only produce triples, so to keep the graph
+    /// names the statement has to be a SELECT query that projects four
+    /// variables (subject, predicate, object and graph), such as the one
+    /// returned by [`Statement::nquads_query`].
+    pub fn evaluate_to_n_quads<'a, W>(
+        self: &Arc<Self>,
+        writer: W,
+        statement: &'a Statement,
+    ) -> Result<Streamer<'a, W>, ekg_error::Error>
+        where
+            W: 'a + Write,
+    {
+        self.evaluate_to_stream(
+            writer,
+            statement,
+            APPLICATION_N_QUADS.deref(),
+            None,
+            None,
         )
     }
 
+    /// Evaluate the given SELECT statement and stream its answers to the
+    /// given writer in the SPARQL 1.1 Query Results CSV format.
+    ///
+    /// Escaping of values is entirely left to RDFox.
+    pub fn evaluate_to_csv<'a, W>(
+        self: &Arc<Self>,
+        writer: W,
+        statement: &'a Statement,
+    ) -> Result<Streamer<'a, W>, ekg_error::Error>
+        where
+            W: 'a + Write,
+    {
+        self.evaluate_to_stream(
+            writer,
+            statement,
+            SPARQL_RESULTS_CSV.deref(),
+            None,
+            None,
+        )
+    }
+
+    /// Evaluate the given SELECT statement and stream its answers to the
+    /// given writer in the SPARQL 1.1 Query Results TSV format.
+    ///
+    /// Escaping of values is entirely left to RDFox.
+    pub fn evaluate_to_tsv<'a, W>(
+        self: &Arc<Self>,
+        writer: W,
+        statement: &'a Statement,
+    ) -> Result<Streamer<'a, W>, ekg_error::Error>
+        where
+            W: 'a + Write,
+    {
+        self.evaluate_to_stream(
+            writer,
+            statement,
+            SPARQL_RESULTS_TSV.deref(),
+            None,
+            None,
+        )
+    }
+
+    /// Return the number of triples in the datastore, in the default graph
+    /// and all named graphs together, restricted to the given fact domain:
+    /// only the asserted facts, only the inferred facts or all facts.
+    ///
+    /// Runs a `COUNT(*)` query in the given transaction.
     pub fn get_triples_count(
         self: &Arc<Self>,
         tx: &Arc<Transaction>,
         fact_domain: FactDomain,
     ) -> Result<usize, ekg_error::Error> {
-        let default_graph = DEFAULT_GRAPH_RDFOX.deref().as_display_iri();
-        Statement::new(
-            &Namespaces::empty()?,
+        self.select_count_in(
+            tx,
             formatdoc!(
                 r##"
-                SELECT ?graph ?s ?p ?o
+                SELECT (COUNT(*) AS ?count)
                 WHERE {{
                     {{
-                        GRAPH ?graph {{ ?s ?p ?o }}
+                        ?s ?p ?o
                     }} UNION {{
-                        ?s ?p ?o .
-                        BIND({default_graph} AS ?graph)
+                        GRAPH ?graph {{ ?s ?p ?o }}
                     }}
                 }}
-            "##
-            )
-                .into(),
-        )?
-            .cursor(
-                self,
-                &Parameters::empty()?.fact_domain(fact_domain)?,
-            )?
-            .count(tx)
+                "##
+            ),
+            fact_domain,
+            "counting triples",
+        )
+        .map(|count| count as usize)
+    }
+
+    /// Same as [`get_triples_count`](Self::get_triples_count), in a read-only
+    /// transaction of its own.
+    pub fn count_triples(self: &Arc<Self>, fact_domain: FactDomain) -> Result<u64, ekg_error::Error> {
+        Transaction::begin_read_only(self)?
+            .execute_and_rollback(|ref tx| self.get_triples_count(tx, fact_domain))
+            .map(|count| count as u64)
+    }
+
+    /// Gather the statistics of the datastore for monitoring, see
+    /// [`Statistics`] for what is reported.
+    ///
+    /// The counts are the result of queries, each in its own read-only
+    /// transaction, so they're not necessarily consistent with each other
+    /// while other connections are making changes. A metric that cannot be
+    /// determined is logged as a warning and left `None`.
+    pub fn statistics(self: &Arc<Self>) -> Result<Statistics, ekg_error::Error> {
+        let metric = |name: &str, value: Result<u64, ekg_error::Error>| {
+            value
+                .map_err(|err| {
+                    tracing::warn!(
+                        target: LOG_TARGET_DATABASE,
+                        conn = self.number,
+                        "Could not determine the {name} of {self}: {err}"
+                    );
+                })
+                .ok()
+        };
+        let statistics = Statistics {
+            triples: metric("number of triples", self.count_triples(FactDomain::ALL)),
+            resources: metric(
+                "number of resources",
+                self.select_count(
+                    formatdoc!(
+                        r##"
+                        SELECT (COUNT(DISTINCT ?resource) AS ?count)
+                        WHERE {{
+                            {{ ?resource ?p ?o }} UNION {{ ?s ?p ?resource }} UNION
+                            {{ GRAPH ?graph {{ ?resource ?p ?o }} }} UNION
+                            {{ GRAPH ?graph {{ ?s ?p ?resource }} }}
+                        }}
+                        "##
+                    ),
+                    FactDomain::ALL,
+                    "counting resources",
+                ),
+            ),
+            predicates: metric(
+                "number of predicates",
+                self.select_count(
+                    formatdoc!(
+                        r##"
+                        SELECT (COUNT(DISTINCT ?predicate) AS ?count)
+                        WHERE {{
+                            {{ ?s ?predicate ?o }} UNION {{ GRAPH ?graph {{ ?s ?predicate ?o }} }}
+                        }}
+                        "##
+                    ),
+                    FactDomain::ALL,
+                    "counting predicates",
+                ),
+            ),
+            rules: metric(
+                "number of rules",
+                self.list_rules().map(|rules| count_rules(rules.as_str())),
+            ),
+            // RDFox reports its memory limit and how much of that is left
+            server_memory_used_bytes: metric(
+                "memory use",
+                self.server_connection
+                    .get_memory_use()
+                    .map(|(max_used_bytes, available_bytes)| {
+                        max_used_bytes.saturating_sub(available_bytes) as u64
+                    }),
+            ),
+        };
+        tracing::debug!(
+            target: LOG_TARGET_DATABASE,
+            conn = self.number,
+            "Statistics of {self}: {statistics}"
+        );
+        Ok(statistics)
+    }
+
+    /// Evaluate the given query, which has to return a single number in its
+    /// first column, in its own read-only transaction and return that number.
+    fn select_count(
+        self: &Arc<Self>,
+        sparql: String,
+        fact_domain: FactDomain,
+        action: &str,
+    ) -> Result<u64, ekg_error::Error> {
+        Transaction::begin_read_only(self)?.execute_and_rollback(|ref tx| {
+            self.select_count_in(tx, sparql, fact_domain, action)
+        })
+    }
+
+    /// Same as `select_count` but in the given transaction.
+    pub(crate) fn select_count_in(
+        self: &Arc<Self>,
+        tx: &Arc<Transaction>,
+        sparql: String,
+        fact_domain: FactDomain,
+        action: &str,
+    ) -> Result<u64, ekg_error::Error> {
+        let statement = Statement::new(&Namespaces::empty()?, sparql.into())?;
+        let mut cursor = statement.cursor(
+            self,
+            &Parameters::empty()?.fact_domain(fact_domain)?,
+        )?;
+        let mut count = 0_u64;
+        cursor.consume(tx, 10, |row| {
+            if let Some(value) = row.lexical_form(0)? {
+                count = value.parse::<u64>().map_err(|err| {
+                    ekg_error::Error::Exception {
+                        action: action.to_string(),
+                        message: format!("unexpected count [{value}]: {err}"),
+                    }
+                })?;
+            }
+            Ok::<(), ekg_error::Error>(())
+        })?;
+        Ok(count)
+    }
+
+    /// Return all graphs that contain at least one triple.
+    ///
+    /// The default graph is represented by [`DEFAULT_GRAPH_RDFOX`] and comes
+    /// first if it's not empty, followed by the named graphs.
+    pub fn list_graphs(self: &Arc<Self>) -> Result<Vec<Graph>, ekg_error::Error> {
+        let prefixes = Namespaces::empty()?;
+        let default_graph_statement = Statement::new(
+            &prefixes,
+            "SELECT ?s WHERE { ?s ?p ?o } LIMIT 1".into(),
+        )?;
+        let named_graphs_statement = Statement::new(
+            &prefixes,
+            "SELECT DISTINCT ?graph WHERE { GRAPH ?graph { ?s ?p ?o } } ORDER BY ?graph".into(),
+        )?;
+        let parameters = Parameters::empty()?.fact_domain(FactDomain::ALL)?;
+        let mut default_graph_cursor = default_graph_statement.cursor(self, &parameters)?;
+        let mut named_graphs_cursor = named_graphs_statement.cursor(self, &parameters)?;
+        let mut graphs = Vec::new();
+        Transaction::begin_read_only(self)?.execute_and_rollback(|ref tx| {
+            if default_graph_cursor.count(tx)? > 0 {
+                graphs.push(DEFAULT_GRAPH_RDFOX.deref().clone());
+            }
+            named_graphs_cursor.consume(tx, usize::MAX, |row| {
+                if let Some(iri) = row.lexical_form(0)? {
+                    graphs.push(graph_from_iri(iri.as_str())?);
+                }
+                Ok::<(), ekg_error::Error>(())
+            })
+        })?;
+        Ok(graphs)
     }
 
     pub fn get_subjects_count(
@@ -481,3 +1765,84 @@ impl DataStoreConnection {
             .count(tx)
     }
 }
+
+/// Return the file extension for the given triple format, the inverse of
+/// [`rdf_format_of`] for the formats that a CONSTRUCT query can produce.
+fn rdf_extension_of(format: &Mime) -> Result<&'static str, ekg_error::Error> {
+    match format.essence_str() {
+        "text/turtle" => Ok("ttl"),
+        "application/n-triples" => Ok("nt"),
+        "application/rdf+xml" => Ok("rdf"),
+        _ => {
+            Err(ekg_error::Error::Exception {
+                action:  "exporting graphs".to_string(),
+                message: format!(
+                    "cannot export graphs as {format}, expected text/turtle, \
+                     application/n-triples or application/rdf+xml"
+                ),
+            })
+        }
+    }
+}
+
+/// Return the RDFox format name for the given RDF file, based on its
+/// extension.
+fn rdf_format_of(file: &Path) -> Result<&'static str, ekg_error::Error> {
+    match file.extension().and_then(|ext| ext.to_str()) {
+        Some("ttl") => Ok("text/turtle"),
+        Some("nt") => Ok("application/n-triples"),
+        Some("nq") => Ok("application/n-quads"),
+        Some("trig") => Ok("application/trig"),
+        Some("rdf") => Ok("application/rdf+xml"),
+        _ => {
+            Err(ekg_error::Error::Exception {
+                action: format!("importing {}", file.display()),
+                message: "unknown RDF file extension, expected one of .ttl, .nt, .nq, .trig or \
+                          .rdf"
+                    .to_string(),
+            })
+        }
+    }
+}
+
+/// Group the given triples by the graph they go into: their own graph if
+/// they have one, otherwise the given graph, in order of first appearance.
+fn triples_by_graph(
+    triples: &[Triple],
+    graph: &GraphTarget,
+) -> Result<Vec<(GraphTarget, Vec<(Term, Term, Term)>)>, ekg_error::Error> {
+    let mut groups: Vec<(GraphTarget, Vec<(Term, Term, Term)>)> = Vec::new();
+    for triple in triples {
+        let graph = match &triple.graph {
+            Some(Term::Iri(iri)) => GraphTarget::from(graph_from_iri(iri)?),
+            Some(term) => {
+                return Err(ekg_error::Error::Exception {
+                    action:  "applying a changeset".to_string(),
+                    message: format!("the graph of a triple has to be an IRI, not {term}"),
+                });
+            }
+            None => graph.clone(),
+        };
+        let spo = (
+            triple.subject.clone(),
+            triple.predicate.clone(),
+            triple.object.clone(),
+        );
+        let iri = graph.graph().as_display_iri().to_string();
+        match groups
+            .iter_mut()
+            .find(|(existing, _)| existing.graph().as_display_iri().to_string() == iri)
+        {
+            Some((_, group)) => group.push(spo),
+            None => groups.push((graph, vec![spo])),
+        }
+    }
+    Ok(groups)
+}
+
+/// The namespace for the given base IRI, if any, as taken by the [`Streamer`].
+fn base_namespace(base_iri: Option<&Iri>) -> Result<Option<Namespace>, ekg_error::Error> {
+    base_iri
+        .map(|iri| Namespace::declare_from_str("base", iri.as_str()))
+        .transpose()
+}
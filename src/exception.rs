@@ -27,7 +27,7 @@ impl CException {
                 } else {
                     Err(ekg_error::Error::Exception {
                         action: action.to_string(),
-                        message: format!("{:}", *c_exception).replace("RDFoxException: ", ""),
+                        message: (*c_exception).message(),
                     })
                 }
             });
@@ -57,6 +57,23 @@ impl CException {
         let what = unsafe { CStr::from_ptr(CException_what(self)) };
         what.to_str()
     }
+
+    /// The name of the exception (which identifies the kind of error, such
+    /// as `RDFoxParseException`) followed by RDFox's own description of
+    /// the error, such as the line and column where parsing failed.
+    ///
+    /// Unlike `Display`, this never fails on invalid UTF-8, so that the
+    /// message of RDFox is never lost.
+    pub fn message(&self) -> String {
+        let name = unsafe { CStr::from_ptr(CException_getExceptionName(self)) }.to_string_lossy();
+        let what = unsafe { CStr::from_ptr(CException_what(self)) }.to_string_lossy();
+        let what = what.trim();
+        if name == "RDFoxException" || name.is_empty() {
+            what.to_string()
+        } else {
+            format!("{name}: {what}")
+        }
+    }
 }
 
 impl Display for CException {
@@ -70,6 +87,107 @@ impl Display for CException {
     }
 }
 
+/// The names of the RDFox exceptions that indicate a temporary condition,
+/// such as a conflict with a concurrent transaction, after which the same
+/// operation could succeed when tried again.
+const RETRYABLE_EXCEPTION_NAMES: [&str; 2] = [
+    "ResourceInUseException",
+    "TemporarilyUnavailableException",
+];
+
+/// Return true if the given error was caused by a temporary condition in
+/// RDFox, like a conflicting concurrent transaction, which makes it
+/// worthwhile to retry the operation (in a fresh transaction).
+///
+/// All other errors, including syntax errors and constraint violations, are
+/// considered genuine errors that would occur again.
+pub fn is_retryable(error: &ekg_error::Error) -> bool {
+    match error {
+        ekg_error::Error::Exception { message, .. } => {
+            RETRYABLE_EXCEPTION_NAMES
+                .iter()
+                .any(|name| message.contains(name))
+        }
+        _ => false,
+    }
+}
+
+/// The start of the message of the error that a statement that ran out of
+/// time fails with, see [`is_timeout`].
+const TIMEOUT_MESSAGE: &str = "statement timed out";
+
+/// The error for a statement that was interrupted after the given timeout.
+pub(crate) fn timeout_error(timeout: std::time::Duration) -> ekg_error::Error {
+    ekg_error::Error::Exception {
+        action:  "evaluating a statement".to_string(),
+        message: format!("{TIMEOUT_MESSAGE} after {timeout:?}"),
+    }
+}
+
+/// Return true if the given error is that of a statement that was
+/// interrupted because it ran longer than its timeout (see
+/// [`Statement::with_timeout`](crate::Statement::with_timeout)).
+pub fn is_timeout(error: &ekg_error::Error) -> bool {
+    match error {
+        ekg_error::Error::Exception { message, .. } => message.starts_with(TIMEOUT_MESSAGE),
+        _ => false,
+    }
+}
+
+const VERSION_MISMATCH_MESSAGE: &str = "version mismatch";
+
+/// The error for a loaded RDFox library whose version diverges from the
+/// expected one by more than the given tolerance.
+pub(crate) fn version_mismatch_error(
+    expected: &str,
+    linked: &str,
+    tolerance: crate::VersionTolerance,
+) -> ekg_error::Error {
+    ekg_error::Error::Exception {
+        action:  "checking the version of RDFox".to_string(),
+        message: format!(
+            "{VERSION_MISMATCH_MESSAGE}: expected RDFox {expected} ({tolerance:?}) but RDFox \
+             {linked} is loaded"
+        ),
+    }
+}
+
+/// Return true if the given error is that of a loaded RDFox library with
+/// another version than the one the bindings were generated for (see
+/// [`Server::check_version`](crate::Server::check_version)).
+pub fn is_version_mismatch(error: &ekg_error::Error) -> bool {
+    match error {
+        ekg_error::Error::Exception { message, .. } => message.starts_with(VERSION_MISMATCH_MESSAGE),
+        _ => false,
+    }
+}
+
+/// The names of the RDFox exceptions that an existing connection raises once
+/// the resource it was connected to is gone, such as a datastore that was
+/// deleted (and possibly created again) since the connection was made.
+const CONNECTION_LOST_EXCEPTION_NAMES: [&str; 1] = ["UnknownResourceException"];
+
+/// Return true if the given error means that the connection to the RDFox
+/// server or datastore is no longer usable, in which case a new connection
+/// (see [`DataStoreConnection::reconnect`](crate::DataStoreConnection::reconnect))
+/// could succeed where the old one failed.
+///
+/// That's the case when the server could not be reached at all, or when
+/// RDFox raised one of the exceptions that an operation on a connection to
+/// a deleted datastore raises. If the datastore is really gone, the
+/// reconnect fails as well and reports why.
+pub fn is_connection_lost(error: &ekg_error::Error) -> bool {
+    match error {
+        ekg_error::Error::CouldNotConnectToServer => true,
+        ekg_error::Error::Exception { message, .. } => {
+            CONNECTION_LOST_EXCEPTION_NAMES
+                .iter()
+                .any(|name| message.contains(name))
+        }
+        _ => false,
+    }
+}
+
 #[macro_export]
 macro_rules! database_call {
     ($function:expr) => {{
@@ -91,3 +209,30 @@ macro_rules! database_call {
         )
     }};
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{is_timeout, is_version_mismatch, timeout_error, version_mismatch_error},
+        crate::VersionTolerance,
+        std::time::Duration,
+    };
+
+    #[test_log::test]
+    fn test_is_timeout() {
+        assert!(is_timeout(&timeout_error(Duration::from_secs(5))));
+        assert!(!is_timeout(&ekg_error::Error::Exception {
+            action:  "evaluating a statement".to_string(),
+            message: "RDFoxParseException: unexpected end of input".to_string(),
+        }));
+        assert!(!is_timeout(&ekg_error::Error::CouldNotConnectToServer));
+    }
+
+    #[test_log::test]
+    fn test_is_version_mismatch() {
+        let error = version_mismatch_error("7.0", "6.3b", VersionTolerance::Minor);
+        assert!(is_version_mismatch(&error));
+        assert!(!is_timeout(&error));
+        assert!(!is_version_mismatch(&timeout_error(Duration::from_secs(5))));
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    serde::{Deserialize, Serialize},
+    std::{fs::File, io::BufReader, path::Path},
+};
+
+/// The name of the manifest file in a directory written by
+/// [`DataStoreConnection::export_all`](crate::DataStoreConnection::export_all).
+pub const EXPORT_MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// The table of contents of a directory with one file per graph, written by
+/// [`DataStoreConnection::export_all`](crate::DataStoreConnection::export_all)
+/// and replayed by
+/// [`DataStoreConnection::import_all`](crate::DataStoreConnection::import_all).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportManifest {
+    /// The MIME type of all files, such as `text/turtle`
+    pub format: String,
+    pub graphs: Vec<ExportedGraph>,
+}
+
+/// One graph in an [`ExportManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedGraph {
+    /// The IRI of the graph, or `default` for the default graph, in the
+    /// syntax accepted by [`parse_graph`](crate::parse_graph)
+    pub graph:   String,
+    /// The name of the file, relative to the directory of the manifest
+    pub file:    String,
+    /// The number of triples that were written to the file
+    pub triples: u64,
+}
+
+impl ExportManifest {
+    pub fn read_from_directory(dir: &Path) -> Result<Self, ekg_error::Error> {
+        let file = dir.join(EXPORT_MANIFEST_FILE_NAME);
+        serde_json::from_reader(BufReader::new(File::open(&file)?)).map_err(|err| {
+            ekg_error::Error::Exception {
+                action:  format!("reading {}", file.display()),
+                message: err.to_string(),
+            }
+        })
+    }
+
+    pub fn write_to_directory(&self, dir: &Path) -> Result<(), ekg_error::Error> {
+        let file = dir.join(EXPORT_MANIFEST_FILE_NAME);
+        serde_json::to_writer_pretty(File::create(&file)?, self).map_err(|err| {
+            ekg_error::Error::Exception {
+                action:  format!("writing {}", file.display()),
+                message: err.to_string(),
+            }
+        })
+    }
+
+    /// The total number of triples in all graphs.
+    pub fn triples(&self) -> u64 { self.graphs.iter().map(|graph| graph.triples).sum() }
+}
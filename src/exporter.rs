@@ -0,0 +1,105 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    crate::{
+        callback_context::CallbackContext,
+        database_call,
+        rdfox_api::{CDataStoreConnection_exportData, COutputStream},
+        DataStoreConnection,
+        Parameters,
+    },
+    ekg_namespace::consts::LOG_TARGET_DATABASE,
+    mime::Mime,
+    std::{ffi::{c_void, CString}, io::Write},
+};
+
+/// An `Exporter` writes the content of a datastore (its facts, or its rules
+/// and axioms, depending on the format) to any [`Write`] via the callbacks
+/// of a `COutputStream`, see
+/// <https://docs.oxfordsemantic.tech/programmatic-access-APIs.html#exporting-data>
+pub(crate) struct Exporter<W: Write> {
+    writer: W,
+    bytes_written: u64,
+}
+
+impl<W: Write> Exporter<W> {
+    /// Export the content of the datastore in the given format to the given
+    /// writer and return the number of bytes that were written.
+    pub(crate) fn run(
+        connection: &DataStoreConnection,
+        writer: W,
+        format: &Mime,
+        parameters: &Parameters,
+    ) -> Result<u64, ekg_error::Error> {
+        assert!(
+            !connection.inner.is_null(),
+            "invalid datastore connection"
+        );
+        let mut exporter = CallbackContext::new(Self { writer, bytes_written: 0 });
+
+        let format_name = CString::new(format.as_ref())?;
+
+        let output_stream = COutputStream {
+            context: exporter.as_context(),
+            flushFn: Some(Self::flush_function),
+            writeFn: Some(Self::write_function),
+        };
+
+        let result = database_call!(
+            format!("Exporting data (format={format_name:?})").as_str(),
+            CDataStoreConnection_exportData(
+                connection.inner,
+                &output_stream,
+                format_name.as_ptr(),
+                parameters.inner.cast_const(),
+            )
+        );
+
+        if let Some(err) = exporter.take_error() {
+            tracing::error!(
+                target: LOG_TARGET_DATABASE,
+                conn = connection.number,
+                "Could not write exported data: {err:?}"
+            );
+            return Err(err.into());
+        }
+        result?;
+
+        tracing::debug!(
+            target: LOG_TARGET_DATABASE,
+            conn = connection.number,
+            "Exported {} bytes",
+            exporter.bytes_written
+        );
+        Ok(exporter.bytes_written)
+    }
+
+    extern "C" fn flush_function(context: *mut c_void) -> bool {
+        let exporter = unsafe { CallbackContext::<Self>::from_context(context) };
+        match exporter.writer.flush() {
+            Ok(()) => true,
+            Err(err) => exporter.fail(err),
+        }
+    }
+
+    extern "C" fn write_function(
+        context: *mut c_void,
+        data: *const c_void,
+        number_of_bytes_to_write: usize,
+    ) -> bool {
+        let exporter = unsafe { CallbackContext::<Self>::from_context(context) };
+        let data = if number_of_bytes_to_write == 0 {
+            &[][..]
+        } else {
+            unsafe { std::slice::from_raw_parts(data as *const u8, number_of_bytes_to_write) }
+        };
+        match exporter.writer.write_all(data) {
+            Ok(()) => {
+                exporter.bytes_written += number_of_bytes_to_write as u64;
+                true
+            }
+            Err(err) => exporter.fail(err),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    crate::Term,
+    ekg_namespace::{consts::DEFAULT_GRAPH_RDFOX, Graph, Namespace},
+    std::{
+        fmt::{Display, Formatter},
+        ops::Deref,
+    },
+};
+
+/// The graph that an import, insert or delete applies to: the default graph
+/// or a named graph, rather than an `Option<&Graph>` of which `None` could
+/// mean either the default graph or all graphs.
+///
+/// Converting [`DEFAULT_GRAPH_RDFOX`] into a `GraphTarget` gives
+/// [`GraphTarget::Default`].
+#[derive(Debug, Clone, Default)]
+pub enum GraphTarget {
+    #[default]
+    Default,
+    Named(Graph),
+}
+
+impl GraphTarget {
+    pub fn default_graph() -> Self { GraphTarget::Default }
+
+    pub fn is_default_graph(&self) -> bool { matches!(self, GraphTarget::Default) }
+
+    /// The graph itself, [`DEFAULT_GRAPH_RDFOX`] for the default graph, as
+    /// RDFox's C API expects it.
+    pub fn graph(&self) -> &Graph {
+        match self {
+            GraphTarget::Default => DEFAULT_GRAPH_RDFOX.deref(),
+            GraphTarget::Named(graph) => graph,
+        }
+    }
+}
+
+impl From<Graph> for GraphTarget {
+    fn from(graph: Graph) -> Self {
+        let default_graph = DEFAULT_GRAPH_RDFOX.deref().as_display_iri().to_string();
+        if graph.as_display_iri().to_string() == default_graph {
+            GraphTarget::Default
+        } else {
+            GraphTarget::Named(graph)
+        }
+    }
+}
+
+impl From<&Graph> for GraphTarget {
+    fn from(graph: &Graph) -> Self { GraphTarget::from(graph.clone()) }
+}
+
+impl Display for GraphTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphTarget::Default => write!(f, "the default graph"),
+            GraphTarget::Named(graph) => write!(f, "{graph}"),
+        }
+    }
+}
+
+/// Parse a [`Graph`] from its IRI, for instance a target graph that is stored
+/// as a plain string in a configuration file. This is the counterpart of
+/// `graph.as_display_iri().to_string()` (`Graph` is defined in
+/// `ekg_namespace` so it cannot implement `FromStr` here).
+///
+/// The IRI can be given with or without the surrounding `<` and `>` and has
+/// to be absolute, i.e. start with a scheme such as `https:`. The default
+/// graph is written as the IRI of [`DEFAULT_GRAPH_RDFOX`] or simply as
+/// `default`.
+pub fn parse_graph(text: &str) -> Result<Graph, ekg_error::Error> {
+    let text = text.trim();
+    if text == "default" {
+        return Ok(DEFAULT_GRAPH_RDFOX.deref().clone());
+    }
+    let iri = text
+        .strip_prefix('<')
+        .and_then(|iri| iri.strip_suffix('>'))
+        .unwrap_or(text);
+    Term::iri(iri)?;
+    if !has_scheme(iri) {
+        return Err(ekg_error::Error::Exception {
+            action:  "parsing a graph IRI".to_string(),
+            message: format!("<{iri}> is not an absolute IRI"),
+        });
+    }
+    if format!("<{iri}>") == DEFAULT_GRAPH_RDFOX.deref().as_display_iri().to_string() {
+        return Ok(DEFAULT_GRAPH_RDFOX.deref().clone());
+    }
+    graph_from_iri(iri)
+}
+
+/// Turn the given absolute graph IRI into a [`Graph`], splitting it into a
+/// namespace and a local name after the last `/` or `#`.
+pub(crate) fn graph_from_iri(iri: &str) -> Result<Graph, ekg_error::Error> {
+    let split_at = iri.rfind(['/', '#']).map(|pos| pos + 1).unwrap_or(iri.len());
+    let (namespace_iri, local_name) = iri.split_at(split_at);
+    Ok(Graph::declare(
+        Namespace::declare_from_str("graph:", namespace_iri)?,
+        local_name,
+    ))
+}
+
+/// RFC 3987: `scheme = ALPHA *( ALPHA / DIGIT / "+" / "-" / "." )` followed
+/// by a colon.
+fn has_scheme(iri: &str) -> bool {
+    let Some((scheme, _)) = iri.split_once(':') else {
+        return false;
+    };
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic()) &&
+        chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test_log::test]
+    fn test_parse_graph() {
+        assert!(super::parse_graph("<https://example.com/graph/g1>").is_ok());
+        assert!(super::parse_graph("https://example.com/graph/g1").is_ok());
+        assert!(super::parse_graph("default").is_ok());
+        assert!(super::parse_graph("graph/g1").is_err());
+        assert!(super::parse_graph("<https://example.com/a b>").is_err());
+    }
+
+    #[test_log::test]
+    fn test_graph_target() {
+        use super::GraphTarget;
+        let default = super::parse_graph("default").unwrap();
+        assert!(GraphTarget::from(default).is_default_graph());
+        let named = super::parse_graph("https://example.com/graph/g1").unwrap();
+        let target = GraphTarget::from(&named);
+        assert!(!target.is_default_graph());
+        assert_eq!(
+            target.graph().as_display_iri().to_string(),
+            "<https://example.com/graph/g1>"
+        );
+    }
+}
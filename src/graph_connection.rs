@@ -2,11 +2,25 @@
 //---------------------------------------------------------------
 
 use {
-    crate::{DataStoreConnection, FactDomain, Namespaces, Parameters, Statement, Transaction},
-    ekg_namespace::{consts::LOG_TARGET_DATABASE, Graph},
-    indoc::formatdoc,
+    crate::{
+        importer::Importer,
+        is_isomorphic,
+        rdfox_api::CUpdateType,
+        DataStoreConnection,
+        FactDomain,
+        GraphTarget,
+        Namespaces,
+        Parameters,
+        Statement,
+        Term,
+        Transaction,
+        APPLICATION_N_TRIPLES,
+    },
+    ekg_namespace::consts::LOG_TARGET_DATABASE,
+    mime::Mime,
     std::{
         fmt::{Display, Formatter},
+        ops::Deref,
         path::Path,
         sync::Arc,
         time::Instant,
@@ -14,14 +28,14 @@ use {
 };
 
 /// A `GraphConnection` is a wrapper around a
-/// [`DataStoreConnection`](DataStoreConnection) with a specific
-/// [`Graph`](Graph) and an optional ontology [`Graph`](Graph).
+/// [`DataStoreConnection`](DataStoreConnection) with a specific graph, which
+/// can be the default graph, and an optional ontology graph.
 #[derive(Debug)]
 pub struct GraphConnection {
     pub data_store_connection: Arc<DataStoreConnection>,
     started_at: Instant,
-    pub graph: Graph,
-    pub ontology_graph: Option<Graph>,
+    pub graph: GraphTarget,
+    pub ontology_graph: Option<GraphTarget>,
 }
 
 impl Display for GraphConnection {
@@ -48,8 +62,8 @@ impl Drop for GraphConnection {
 impl GraphConnection {
     pub fn new(
         data_store_connection: Arc<DataStoreConnection>,
-        graph: Graph,
-        ontology_graph: Option<Graph>,
+        graph: GraphTarget,
+        ontology_graph: Option<GraphTarget>,
     ) -> Arc<Self> {
         let result = Self {
             data_store_connection,
@@ -61,6 +75,15 @@ impl GraphConnection {
         Arc::new(result)
     }
 
+    /// Create a `GraphConnection` for the default graph of the datastore.
+    pub fn for_default_graph(data_store_connection: Arc<DataStoreConnection>) -> Arc<Self> {
+        Self::new(data_store_connection, GraphTarget::Default, None)
+    }
+
+    /// Return true if this is a connection to the default graph rather than
+    /// to a named graph.
+    pub fn is_default_graph(&self) -> bool { self.graph.is_default_graph() }
+
     /// Create a clone with another `DataStoreConnection`
     pub fn with_data_store_connection(
         &self,
@@ -103,33 +126,203 @@ impl GraphConnection {
             .import_rdf_from_directory(root, &self.graph)
     }
 
-    /// Get the number of triples using the given transaction.
+    /// Write all triples in the graph to the given writer in the given
+    /// format (such as Turtle or N-Triples), for instance to make a backup
+    /// of a single graph.
+    ///
+    /// Returns the number of triples that were written.
+    pub fn export<W>(&self, writer: W, format: &Mime) -> Result<u64, ekg_error::Error>
+        where W: std::io::Write {
+        let sparql = format!(
+            "CONSTRUCT {{ ?s ?p ?o }} WHERE {{ {} }}",
+            self.triple_pattern()
+        );
+        let statement = Statement::new(&Namespaces::empty()?, sparql.into())?;
+        let streamer = self
+            .data_store_connection
+            .evaluate_to_stream(writer, &statement, format, None, None)?;
+        Ok(streamer.number_of_solutions())
+    }
+
+    /// Return the number of triples in the graph, counted in a read-only
+    /// transaction of its own, see [`get_triples_count`](Self::get_triples_count).
+    pub fn count_triples(&self, fact_domain: FactDomain) -> Result<usize, ekg_error::Error> {
+        Transaction::begin_read_only(&self.data_store_connection)?
+            .execute_and_rollback(|ref tx| self.get_triples_count(tx, fact_domain))
+    }
+
+    /// Return all triples in the graph, resolved into terms, read in a
+    /// read-only transaction of its own.
+    pub fn triples(
+        &self,
+        fact_domain: FactDomain,
+    ) -> Result<Vec<(Term, Term, Term)>, ekg_error::Error> {
+        let statement = Statement::new(
+            &Namespaces::empty()?,
+            format!("SELECT ?s ?p ?o WHERE {{ {} }}", self.triple_pattern()).into(),
+        )?;
+        let mut cursor = statement.cursor(
+            &self.data_store_connection,
+            &Parameters::empty()?.fact_domain(fact_domain)?,
+        )?;
+        let result_set = Transaction::begin_read_only(&self.data_store_connection)?
+            .execute_and_rollback(|ref tx| cursor.result_set(tx, usize::MAX))?;
+        result_set
+            .into_iter()
+            .map(|row| {
+                match <[Option<Term>; 3]>::try_from(row) {
+                    Ok([Some(subject), Some(predicate), Some(object)]) => {
+                        Ok((subject, predicate, object))
+                    }
+                    _ => {
+                        Err(ekg_error::Error::Exception {
+                            action:  format!("reading the triples of {}", self.graph),
+                            message: "expected a subject, predicate and object".to_string(),
+                        })
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Return true if this graph and the given graph, which may well be in
+    /// another datastore, contain the same facts (asserted and derived) up
+    /// to the labels of their blank nodes, see [`is_isomorphic`], for
+    /// instance to check an export and import round trip in a test.
+    pub fn is_isomorphic_to(&self, other: &GraphConnection) -> Result<bool, ekg_error::Error> {
+        Ok(is_isomorphic(
+            &self.triples(FactDomain::ALL)?,
+            &other.triples(FactDomain::ALL)?,
+        ))
+    }
+
+    /// The graph pattern that matches every triple `?s ?p ?o` in the graph.
+    fn triple_pattern(&self) -> String {
+        match &self.graph {
+            GraphTarget::Default => "?s ?p ?o".to_string(),
+            GraphTarget::Named(graph) => format!("GRAPH {} {{ ?s ?p ?o }}", graph.as_display_iri()),
+        }
+    }
+
+    /// Add the given triple to the graph, as part of the given read/write
+    /// transaction.
+    pub fn insert(
+        &self,
+        tx: &Arc<Transaction>,
+        subject: &Term,
+        predicate: &Term,
+        object: &Term,
+    ) -> Result<(), ekg_error::Error> {
+        self.insert_all(tx, &[(subject.clone(), predicate.clone(), object.clone())])
+    }
+
+    /// Remove the given triple from the graph, as part of the given
+    /// read/write transaction.
+    pub fn remove(
+        &self,
+        tx: &Arc<Transaction>,
+        subject: &Term,
+        predicate: &Term,
+        object: &Term,
+    ) -> Result<(), ekg_error::Error> {
+        self.remove_all(tx, &[(subject.clone(), predicate.clone(), object.clone())])
+    }
+
+    /// Add all the given triples to the graph in one go.
     ///
-    /// TODO: Implement this with SPARQL COUNT (and compare performance)
+    /// The C API has no call to add a single fact, so the triples are
+    /// written out as N-Triples text and fed to RDFox's bulk import, which
+    /// parses them again. That still avoids parsing and evaluating a SPARQL
+    /// `INSERT DATA` statement.
+    pub fn insert_all(
+        &self,
+        tx: &Arc<Transaction>,
+        triples: &[(Term, Term, Term)],
+    ) -> Result<(), ekg_error::Error> {
+        self.update_triples(
+            tx,
+            triples,
+            CUpdateType::UPDATE_TYPE_ADDITION,
+        )
+    }
+
+    /// Remove all the given triples from the graph in one go, written out as
+    /// N-Triples text just like [`insert_all`](Self::insert_all) does.
+    pub fn remove_all(
+        &self,
+        tx: &Arc<Transaction>,
+        triples: &[(Term, Term, Term)],
+    ) -> Result<(), ekg_error::Error> {
+        self.update_triples(
+            tx,
+            triples,
+            CUpdateType::UPDATE_TYPE_DELETION,
+        )
+    }
+
+    fn update_triples(
+        &self,
+        tx: &Arc<Transaction>,
+        triples: &[(Term, Term, Term)],
+        update_type: CUpdateType,
+    ) -> Result<(), ekg_error::Error> {
+        if !tx.connection.same(&self.data_store_connection) {
+            return Err(invalid_triple(
+                "the transaction belongs to another connection".to_string(),
+            ));
+        }
+        if tx.is_read_only() {
+            return Err(invalid_triple(
+                "the transaction is read-only".to_string(),
+            ));
+        }
+        let mut content = String::new();
+        for (subject, predicate, object) in triples {
+            if subject.is_literal() {
+                return Err(invalid_triple(format!(
+                    "subject {subject} is a literal"
+                )));
+            }
+            if !predicate.is_iri() {
+                return Err(invalid_triple(format!(
+                    "predicate {predicate} is not an IRI"
+                )));
+            }
+            content.push_str(&format!(
+                "{} {} {} .\n",
+                subject.as_ntriples(),
+                predicate.as_ntriples(),
+                object.as_ntriples()
+            ));
+        }
+        Importer::run(
+            &self.data_store_connection,
+            content.as_bytes(),
+            APPLICATION_N_TRIPLES.deref(),
+            &self.graph,
+            update_type,
+        )
+        .map(|_| ())
+    }
+
+    /// Get the number of triples using the given transaction, with a
+    /// SPARQL `COUNT` so that the triples themselves are not read.
     pub fn get_triples_count(
         &self,
         tx: &Arc<Transaction>,
         fact_domain: FactDomain,
     ) -> Result<usize, ekg_error::Error> {
-        Statement::new(
-            &Namespaces::empty()?,
-            formatdoc!(
-                r##"
-                SELECT ?s ?p ?o
-                FROM {:}
-                WHERE {{
-                    ?s ?p ?o .
-                }}
-            "##,
-                self.graph.as_display_iri()
+        self.data_store_connection
+            .select_count_in(
+                tx,
+                format!(
+                    "SELECT (COUNT(*) AS ?count) WHERE {{ {} }}",
+                    self.triple_pattern()
+                ),
+                fact_domain,
+                format!("counting the triples of {}", self.graph).as_str(),
             )
-                .into(),
-        )?
-            .cursor(
-                &self.data_store_connection,
-                &Parameters::empty()?.fact_domain(fact_domain)?,
-            )?
-            .count(tx)
+            .map(|count| count as usize)
     }
 
     // pub fn get_subjects_count(&self, fact_domain: FactDomain) ->
@@ -195,3 +388,7 @@ impl GraphConnection {
     //         .count()
     // }
 }
+
+fn invalid_triple(message: String) -> ekg_error::Error {
+    ekg_error::Error::Exception { action: "updating triples in a graph".to_string(), message }
+}
@@ -0,0 +1,31 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use std::fmt::{Display, Formatter};
+
+/// What was read when importing data with
+/// [`DataStoreConnection::import_data_with_report`](crate::DataStoreConnection::import_data_with_report).
+///
+/// The C API of RDFox does not report how many facts an import added or
+/// changed, nor which lines it rejected, so this only tells what was fed to
+/// RDFox. Rejected values are only visible in RDFox's own log, with
+/// [`ImportErrorMode::Warn`](crate::ImportErrorMode::Warn).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ImportReport {
+    /// The number of bytes that were read from the input
+    pub bytes_read: u64,
+    /// The number of newline characters that were read from the input,
+    /// counting every line, including comments, blank lines and lines that
+    /// RDFox rejected
+    pub lines_read: u64,
+}
+
+impl Display for ImportReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} lines ({} bytes) read",
+            self.lines_read, self.bytes_read
+        )
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    crate::{
+        callback_context::CallbackContext,
+        database_call,
+        rdfox_api::{CDataStoreConnection_importData, CInputStream, CUpdateType},
+        DataStoreConnection,
+        GraphTarget,
+    },
+    ekg_namespace::consts::{DEFAULT_BASE_IRI, LOG_TARGET_DATABASE},
+    mime::Mime,
+    std::{
+        ffi::{c_void, CString},
+        io::{BufReader, ErrorKind, Read},
+    },
+};
+
+/// The default capacity of the buffer between the reader and RDFox, see
+/// [`DataStoreConnection::import_data_from_reader_with_buffer_size`].
+pub const DEFAULT_READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// An `Importer` is the counterpart of the [`Streamer`](crate::Streamer):
+/// it feeds the content of any [`Read`] to RDFox via the read callbacks of
+/// a `CInputStream`, so that RDFox pulls the data in chunks and memory usage
+/// stays constant regardless of the size of the input.
+pub(crate) struct Importer<'p, R: Read> {
+    reader: BufReader<R>,
+    bytes_read: u64,
+    lines_read: u64,
+    progress: Option<&'p mut dyn FnMut(u64, u64)>,
+}
+
+impl<'p, R: Read> Importer<'p, R> {
+    /// Import everything that can be read from the given reader, in the given
+    /// format, into the given graph. With `UPDATE_TYPE_DELETION` the facts
+    /// (or rules) that are read are deleted rather than added.
+    pub(crate) fn run(
+        connection: &DataStoreConnection,
+        reader: R,
+        format: &Mime,
+        graph: &GraphTarget,
+        update_type: CUpdateType,
+    ) -> Result<u64, ekg_error::Error> {
+        Self::run_with_progress(
+            connection,
+            reader,
+            format,
+            graph,
+            update_type,
+            DEFAULT_READ_BUFFER_SIZE,
+            None,
+        )
+    }
+
+    /// Same as [`run`](Self::run) but reading from the reader through a
+    /// buffer of the given size and calling the given callback with the
+    /// number of bytes and lines read so far, after every chunk of data that
+    /// RDFox pulls in.
+    pub(crate) fn run_with_progress(
+        connection: &DataStoreConnection,
+        reader: R,
+        format: &Mime,
+        graph: &GraphTarget,
+        update_type: CUpdateType,
+        read_buffer_size: usize,
+        progress: Option<&'p mut dyn FnMut(u64, u64)>,
+    ) -> Result<u64, ekg_error::Error> {
+        assert!(
+            !connection.inner.is_null(),
+            "invalid datastore connection"
+        );
+        let span = tracing::debug_span!(
+            target: LOG_TARGET_DATABASE,
+            "import",
+            conn = connection.number,
+            graph = %graph,
+            format = %format,
+            bytes = tracing::field::Empty,
+            elapsed_ms = tracing::field::Empty,
+        );
+        let _entered = span.enter();
+        let started_at = std::time::Instant::now();
+        let mut importer = CallbackContext::new(Self {
+            reader: BufReader::with_capacity(read_buffer_size.max(1), reader),
+            bytes_read: 0,
+            lines_read: 0,
+            progress,
+        });
+
+        let c_graph_name = graph.graph().as_c_string()?;
+        let c_base_iri = CString::new(DEFAULT_BASE_IRI)?;
+        let format_name = CString::new(format.as_ref())?;
+
+        let input_stream = CInputStream {
+            context: importer.as_context(),
+            rewindFn: Some(Self::rewind_function),
+            readFn: Some(Self::read_function),
+        };
+
+        let result = database_call!(
+            format!("Importing data from a stream into {graph} (format={format_name:?})").as_str(),
+            CDataStoreConnection_importData(
+                connection.inner,
+                c_graph_name.as_ptr(),
+                update_type,
+                &input_stream,
+                c_base_iri.as_ptr(),
+                format_name.as_ptr(),
+            )
+        );
+
+        if let Some(err) = importer.take_error() {
+            tracing::error!(
+                target: LOG_TARGET_DATABASE,
+                conn = connection.number,
+                "Could not read import data: {err:?}"
+            );
+            return Err(err.into());
+        }
+        result?;
+
+        span.record("bytes", importer.bytes_read);
+        span.record("elapsed_ms", started_at.elapsed().as_millis() as u64);
+        tracing::debug!(
+            target: LOG_TARGET_DATABASE,
+            conn = connection.number,
+            "Imported {} bytes into {graph}",
+            importer.bytes_read
+        );
+        Ok(importer.bytes_read)
+    }
+
+    /// A generic `Read` cannot go back to the start so we tell RDFox that
+    /// rewinding is not supported.
+    extern "C" fn rewind_function(_context: *mut c_void) -> bool { false }
+
+    extern "C" fn read_function(
+        context: *mut c_void,
+        data: *mut c_void,
+        number_of_bytes_to_read: usize,
+        bytes_read: *mut usize,
+    ) -> bool {
+        let importer = unsafe { CallbackContext::<Self>::from_context(context) };
+        let buffer = if number_of_bytes_to_read == 0 {
+            &mut [][..]
+        } else {
+            unsafe { std::slice::from_raw_parts_mut(data as *mut u8, number_of_bytes_to_read) }
+        };
+        // RDFox takes fewer bytes than it asked for as the end of the input,
+        // so keep reading until the buffer is full or the reader is exhausted
+        let mut len = 0_usize;
+        while len < buffer.len() {
+            match importer.reader.read(&mut buffer[len..]) {
+                Ok(0) => break,
+                Ok(read) => len += read,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    unsafe { *bytes_read = 0 };
+                    return importer.fail(err);
+                }
+            }
+        }
+        unsafe { *bytes_read = len };
+        importer.bytes_read += len as u64;
+        importer.lines_read += buffer[..len].iter().filter(|byte| **byte == b'\n').count() as u64;
+        match importer.report_progress() {
+            Ok(()) => true,
+            Err(err) => importer.fail(err),
+        }
+    }
+
+    /// Call the progress callback, if any, without letting a panic in it
+    /// unwind into RDFox's C++ code: a panic aborts the import instead.
+    fn report_progress(&mut self) -> std::io::Result<()> {
+        let (bytes_read, lines_read) = (self.bytes_read, self.lines_read);
+        let Some(progress) = self.progress.as_mut() else {
+            return Ok(());
+        };
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            progress(bytes_read, lines_read)
+        }))
+        .map_err(|_| {
+            std::io::Error::new(
+                ErrorKind::Other,
+                "the import progress callback panicked",
+            )
+        })
+    }
+}
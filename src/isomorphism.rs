@@ -0,0 +1,274 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    crate::Term,
+    std::{
+        collections::{hash_map::DefaultHasher, HashMap, HashSet},
+        hash::{Hash, Hasher},
+    },
+};
+
+type Spo = (Term, Term, Term);
+
+/// Return true if the two sets of triples are isomorphic, that is equal
+/// once the blank nodes of one are relabeled with a one-to-one mapping onto
+/// the blank nodes of the other. Duplicate triples are ignored.
+///
+/// Triples without blank nodes are simply compared as sets. The blank nodes
+/// are first partitioned by iteratively hashing their neighbourhoods (colour
+/// refinement), after which only blank nodes with the same hash are tried
+/// against each other. That's fast for the graphs one meets in practice,
+/// even though the problem is hard in general.
+///
+/// Any collection of triples can be compared, such as slices, vectors or
+/// sets, so this does not need a connection to a datastore, see
+/// [`GraphConnection::is_isomorphic_to`](crate::GraphConnection::is_isomorphic_to)
+/// to compare two graphs in RDFox.
+pub fn is_isomorphic<'a, L, R>(left: L, right: R) -> bool
+    where
+        L: IntoIterator<Item = &'a Spo>,
+        R: IntoIterator<Item = &'a Spo>,
+{
+    let left = left.into_iter().collect::<HashSet<_>>();
+    let right = right.into_iter().collect::<HashSet<_>>();
+    if left.len() != right.len() {
+        return false;
+    }
+    let (left_ground, left_blank): (Vec<&Spo>, Vec<&Spo>) =
+        left.iter().copied().partition(|triple| !has_blank_node(triple));
+    let right_blank = right
+        .iter()
+        .copied()
+        .filter(|triple| has_blank_node(triple))
+        .collect::<Vec<_>>();
+    if left_blank.len() != right_blank.len() ||
+        !left_ground.iter().all(|triple| right.contains(*triple))
+    {
+        return false;
+    }
+    if left_blank.is_empty() {
+        return true;
+    }
+    let left_colours = colours(left_blank.as_slice());
+    let right_colours = colours(right_blank.as_slice());
+    let mut left_histogram = left_colours.values().copied().collect::<Vec<_>>();
+    let mut right_histogram = right_colours.values().copied().collect::<Vec<_>>();
+    left_histogram.sort_unstable();
+    right_histogram.sort_unstable();
+    if left_histogram != right_histogram {
+        return false;
+    }
+    let mut candidates: HashMap<u64, Vec<&str>> = HashMap::new();
+    for (node, colour) in right_colours.iter() {
+        candidates.entry(*colour).or_default().push(*node);
+    }
+    // Map the blank nodes with the fewest candidates first
+    let mut order = left_colours.keys().copied().collect::<Vec<_>>();
+    order.sort_by_key(|node| (candidates[&left_colours[node]].len(), *node));
+    let mut search = Search {
+        left_blank: left_blank.as_slice(),
+        right: &right,
+        left_colours: &left_colours,
+        candidates: &candidates,
+        mapping: HashMap::new(),
+        used: HashSet::new(),
+    };
+    search.extend(order.as_slice())
+}
+
+struct Search<'a, 'b> {
+    left_blank:   &'b [&'a Spo],
+    right:        &'b HashSet<&'a Spo>,
+    left_colours: &'b HashMap<&'a str, u64>,
+    candidates:   &'b HashMap<u64, Vec<&'a str>>,
+    mapping:      HashMap<&'a str, &'a str>,
+    used:         HashSet<&'a str>,
+}
+
+impl<'a, 'b> Search<'a, 'b> {
+    /// Try to map the given blank nodes, in order, on top of the current
+    /// mapping, backtracking when a mapped triple is missing on the right.
+    fn extend(&mut self, nodes: &[&'a str]) -> bool {
+        let Some((node, rest)) = nodes.split_first() else {
+            return true;
+        };
+        let candidates = self.candidates;
+        for candidate in candidates[&self.left_colours[node]].iter() {
+            if self.used.contains(candidate) {
+                continue;
+            }
+            self.mapping.insert(*node, *candidate);
+            self.used.insert(*candidate);
+            if self.is_consistent(node) && self.extend(rest) {
+                return true;
+            }
+            self.mapping.remove(node);
+            self.used.remove(candidate);
+        }
+        false
+    }
+
+    /// Check the triples of the given blank node of which all blank nodes
+    /// have been mapped.
+    fn is_consistent(&self, node: &str) -> bool {
+        self.left_blank
+            .iter()
+            .filter(|triple| mentions(triple, node))
+            .all(|triple| {
+                match self.map_triple(triple) {
+                    Some(mapped) => self.right.contains(&mapped),
+                    None => true,
+                }
+            })
+    }
+
+    fn map_triple(&self, (subject, predicate, object): &Spo) -> Option<Spo> {
+        Some((
+            self.map_term(subject)?,
+            self.map_term(predicate)?,
+            self.map_term(object)?,
+        ))
+    }
+
+    fn map_term(&self, term: &Term) -> Option<Term> {
+        match term {
+            Term::BlankNode(label) => {
+                self.mapping
+                    .get(label.as_str())
+                    .map(|mapped| Term::BlankNode(mapped.to_string()))
+            }
+            _ => Some(term.clone()),
+        }
+    }
+}
+
+/// Hash every blank node in the given triples by its neighbourhood, refined
+/// until the number of distinct hashes no longer grows.
+fn colours<'a>(triples: &[&'a Spo]) -> HashMap<&'a str, u64> {
+    let mut colours = triples
+        .iter()
+        .copied()
+        .flat_map(|(subject, predicate, object)| [subject, predicate, object])
+        .filter_map(|term| {
+            match term {
+                Term::BlankNode(label) => Some((label.as_str(), 0_u64)),
+                _ => None,
+            }
+        })
+        .collect::<HashMap<_, _>>();
+    loop {
+        let refined = colours
+            .iter()
+            .map(|(node, colour)| {
+                let mut signatures = triples
+                    .iter()
+                    .filter(|triple| mentions(triple, node))
+                    .map(|(subject, predicate, object)| {
+                        hash_of(&[
+                            term_signature(subject, node, &colours),
+                            term_signature(predicate, node, &colours),
+                            term_signature(object, node, &colours),
+                        ])
+                    })
+                    .collect::<Vec<_>>();
+                signatures.sort_unstable();
+                (*node, hash_of(&(colour, signatures)))
+            })
+            .collect::<HashMap<_, _>>();
+        let is_stable = number_of_colours(&refined) == number_of_colours(&colours);
+        colours = refined;
+        if is_stable {
+            return colours;
+        }
+    }
+}
+
+/// Describe a term of a triple as seen from the given blank node: the node
+/// itself, another blank node by its colour, or any other term by its value.
+fn term_signature(term: &Term, node: &str, colours: &HashMap<&str, u64>) -> (u8, u64) {
+    match term {
+        Term::BlankNode(label) if label == node => (0, 0),
+        Term::BlankNode(label) => (1, colours[label.as_str()]),
+        _ => (2, hash_of(term)),
+    }
+}
+
+fn number_of_colours(colours: &HashMap<&str, u64>) -> usize {
+    colours.values().collect::<HashSet<_>>().len()
+}
+
+fn has_blank_node((subject, predicate, object): &Spo) -> bool {
+    subject.is_blank_node() || predicate.is_blank_node() || object.is_blank_node()
+}
+
+fn mentions((subject, predicate, object): &Spo, node: &str) -> bool {
+    [subject, predicate, object]
+        .iter()
+        .any(|term| matches!(term, Term::BlankNode(label) if label == node))
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::is_isomorphic, crate::Term, std::collections::HashSet};
+
+    fn triple(subject: &str, predicate: &str, object: &str) -> (Term, Term, Term) {
+        let term = |value: &str| {
+            match value.strip_prefix("_:") {
+                Some(label) => Term::blank_node(label).unwrap(),
+                None => Term::iri(format!("https://example.com/{value}").as_str()).unwrap(),
+            }
+        };
+        (term(subject), term(predicate), term(object))
+    }
+
+    #[test_log::test]
+    fn test_is_isomorphic() {
+        let ground = vec![triple("a", "p", "b"), triple("b", "p", "c")];
+        assert!(is_isomorphic(&ground, &[ground[1].clone(), ground[0].clone()]));
+        assert!(!is_isomorphic(&ground, &ground[..1]));
+        assert!(is_isomorphic(
+            &ground.iter().cloned().collect::<HashSet<_>>(),
+            &ground
+        ));
+
+        let left = vec![
+            triple("_:x", "p", "_:y"),
+            triple("_:y", "p", "_:x"),
+            triple("_:x", "q", "a"),
+        ];
+        let relabeled = vec![
+            triple("_:b2", "p", "_:b1"),
+            triple("_:b1", "p", "_:b2"),
+            triple("_:b1", "q", "a"),
+        ];
+        assert!(is_isomorphic(&left, &relabeled));
+        let different = vec![
+            triple("_:b2", "p", "_:b1"),
+            triple("_:b1", "p", "_:b2"),
+            triple("_:b1", "q", "b"),
+        ];
+        assert!(!is_isomorphic(&left, &different));
+
+        // Two triangles and a hexagon cannot be told apart by colour
+        // refinement alone, only by the search
+        fn cycle(labels: &[&str]) -> Vec<(Term, Term, Term)> {
+            (0..labels.len())
+                .map(|i| triple(labels[i], "p", labels[(i + 1) % labels.len()]))
+                .collect()
+        }
+        let mut triangles = cycle(&["_:a", "_:b", "_:c"]);
+        triangles.extend(cycle(&["_:d", "_:e", "_:f"]));
+        let hexagon = cycle(&["_:1", "_:2", "_:3", "_:4", "_:5", "_:6"]);
+        assert!(!is_isomorphic(&triangles, &hexagon));
+        let mut other_triangles = cycle(&["_:6", "_:4", "_:2"]);
+        other_triangles.extend(cycle(&["_:5", "_:3", "_:1"]));
+        assert!(is_isomorphic(&triangles, &other_triangles));
+    }
+}
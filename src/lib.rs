@@ -7,40 +7,109 @@
 extern crate core;
 
 pub use {
+    cancellation::CancellationToken,
     class_report::ClassReport,
-    connectable_data_store::ConnectableDataStore,
-    cursor::{Cursor, CursorRow, OpenedCursor},
+    connectable_data_store::{ConnectableDataStore, ConnectionPool},
+    consts::{
+        APPLICATION_N_QUADS,
+        APPLICATION_N_TRIPLES,
+        APPLICATION_TRIG,
+        APPLICATION_X_DATALOG,
+        PREFIX_DCTERMS,
+        PREFIX_FOAF,
+        PREFIX_SH,
+        RDF_TYPE,
+        SPARQL_RESULTS_CSV,
+        SPARQL_RESULTS_TSV,
+    },
+    cursor::{AnswerRow, Cursor, CursorRow, OpenedCursor, ResultSet, Triple, TripleCursor},
     data_store::DataStore,
     data_store_connection::DataStoreConnection,
+    ekg_namespace::consts::{PREFIX_OWL, PREFIX_RDF, PREFIX_RDFS, PREFIX_SKOS, PREFIX_XSD},
+    exception::{is_connection_lost, is_retryable, is_timeout, is_version_mismatch},
+    export_manifest::{ExportedGraph, ExportManifest, EXPORT_MANIFEST_FILE_NAME},
+    graph::{parse_graph, GraphTarget},
     graph_connection::GraphConnection,
+    import_report::ImportReport,
+    importer::DEFAULT_READ_BUFFER_SIZE,
+    isomorphism::is_isomorphic,
     license::{find_license, RDFOX_DEFAULT_LICENSE_FILE_NAME, RDFOX_HOME},
+    materialization_report::MaterializationReport,
     mime::Mime,
     namespaces::{Namespaces, NamespacesBuilder},
-    parameters::{DataStoreType, FactDomain, Parameters, PersistenceMode},
+    parameters::{
+        DataStoreType,
+        EqualityMode,
+        FactDomain,
+        ImportErrorMode,
+        Parameters,
+        ParametersBuilder,
+        PersistenceMode,
+    },
+    query_page::QueryPage,
+    rdf_store::RdfStore,
     role_creds::RoleCreds,
-    server::Server,
+    server::{Server, VersionTolerance, RDFOX_VERSION_EXPECTED},
     server_connection::ServerConnection,
-    statement::Statement,
+    statement::{QueryType, SelectBuilder, Statement},
+    statement_template::StatementTemplate,
+    statistics::Statistics,
     streamer::Streamer,
-    transaction::Transaction,
+    sync_connection::{SyncConnection, SyncConnectionGuard},
+    term::Term,
+    transaction::{CommitInfo, Transaction, TransactionScope, TransactionType},
+    update_report::UpdateReport,
 };
 
+/// The error type of every fallible function in this crate, re-exported
+/// from the `ekg-error` crate (with its `rdfox` feature) so that
+/// applications don't need to depend on it themselves. Errors that RDFox
+/// raises are reported as [`Error::Exception`].
+pub use ekg_error::Error;
+
+#[cfg(feature = "testing")]
+pub use mock_store::MockStore;
+
+#[cfg(feature = "async")]
+mod async_cursor;
+#[cfg(feature = "async")]
+mod async_streamer;
+mod callback_context;
+mod cancellation;
 mod class_report;
 mod connectable_data_store;
+mod consts;
 mod cursor;
 mod data_store;
 mod data_store_connection;
 mod exception;
+mod export_manifest;
+mod exporter;
+mod graph;
 mod graph_connection;
+mod import_report;
+mod importer;
+mod isomorphism;
 mod license;
+mod materialization_report;
+#[cfg(feature = "testing")]
+mod mock_store;
 mod namespaces;
 mod parameters;
+pub mod prelude;
+mod query_page;
+mod rdf_store;
 mod role_creds;
 mod server;
 mod server_connection;
 mod statement;
+mod statement_template;
+mod statistics;
 mod streamer;
+mod sync_connection;
+mod term;
 mod transaction;
+mod update_report;
 
 #[allow(dead_code)]
 #[allow(non_camel_case_types)]
@@ -0,0 +1,29 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
+
+/// What happened when the datastore was explicitly brought up to date with
+/// its rules, see
+/// [`DataStoreConnection::materialize`](crate::DataStoreConnection::materialize).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MaterializationReport {
+    /// The number of facts that were derived (or, if negative, retracted),
+    /// only counted by
+    /// [`DataStoreConnection::materialize_and_count`](crate::DataStoreConnection::materialize_and_count)
+    pub facts_added: Option<i64>,
+    /// How long reasoning took
+    pub duration:    Duration,
+}
+
+impl Display for MaterializationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.facts_added {
+            Some(facts_added) => write!(f, "{facts_added} facts added in {:?}", self.duration),
+            None => write!(f, "done in {:?}", self.duration),
+        }
+    }
+}
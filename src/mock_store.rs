@@ -0,0 +1,114 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    crate::{RdfStore, ResultSet, UpdateReport},
+    std::{collections::HashMap, sync::Mutex},
+};
+
+/// An [`RdfStore`] that answers with canned results, to unit-test the logic
+/// of code that queries a datastore without starting an RDFox server (the
+/// RDFox library is still linked though, see [`RdfStore`]).
+///
+/// Statements are matched on their text with all whitespace collapsed, so
+/// that indentation and line breaks don't matter. A statement without a
+/// canned result is an error, and every statement is recorded so that tests
+/// can check what was executed.
+#[derive(Debug, Default)]
+pub struct MockStore {
+    selects:  Mutex<HashMap<String, ResultSet>>,
+    asks:     Mutex<HashMap<String, bool>>,
+    updates:  Mutex<HashMap<String, UpdateReport>>,
+    executed: Mutex<Vec<String>>,
+}
+
+impl MockStore {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn on_select(self, sparql: &str, result_set: ResultSet) -> Self {
+        self.selects
+            .lock()
+            .unwrap()
+            .insert(normalize(sparql), result_set);
+        self
+    }
+
+    pub fn on_ask(self, sparql: &str, answer: bool) -> Self {
+        self.asks
+            .lock()
+            .unwrap()
+            .insert(normalize(sparql), answer);
+        self
+    }
+
+    pub fn on_update(self, sparql: &str, report: UpdateReport) -> Self {
+        self.updates
+            .lock()
+            .unwrap()
+            .insert(normalize(sparql), report);
+        self
+    }
+
+    /// The statements that were executed so far, in order, with their
+    /// whitespace collapsed.
+    pub fn executed(&self) -> Vec<String> { self.executed.lock().unwrap().clone() }
+
+    fn answer<T: Clone>(
+        &self,
+        answers: &Mutex<HashMap<String, T>>,
+        sparql: &str,
+    ) -> Result<T, ekg_error::Error> {
+        let sparql = normalize(sparql);
+        self.executed.lock().unwrap().push(sparql.clone());
+        answers
+            .lock()
+            .unwrap()
+            .get(&sparql)
+            .cloned()
+            .ok_or_else(|| {
+                ekg_error::Error::Exception {
+                    action:  "evaluating a statement on a mock store".to_string(),
+                    message: format!("no canned result for: {sparql}"),
+                }
+            })
+    }
+}
+
+impl RdfStore for MockStore {
+    fn select(&self, sparql: &str) -> Result<ResultSet, ekg_error::Error> {
+        self.answer(&self.selects, sparql)
+    }
+
+    fn ask(&self, sparql: &str) -> Result<bool, ekg_error::Error> { self.answer(&self.asks, sparql) }
+
+    fn update(&self, sparql: &str) -> Result<UpdateReport, ekg_error::Error> {
+        self.answer(&self.updates, sparql)
+    }
+}
+
+fn normalize(sparql: &str) -> String { sparql.split_whitespace().collect::<Vec<_>>().join(" ") }
+
+#[cfg(test)]
+mod tests {
+    use crate::{MockStore, RdfStore, ResultSet, Term};
+
+    #[test_log::test]
+    fn test_mock_store() {
+        let labels = ResultSet {
+            variables: vec!["label".to_string()],
+            rows:      vec![vec![Some(Term::literal("x"))]],
+        };
+        let store = MockStore::new()
+            .on_select("SELECT ?label WHERE { ?s rdfs:label ?label }", labels)
+            .on_ask("ASK { ?s ?p ?o }", true);
+        let labels = store
+            .select("SELECT ?label\n  WHERE {\n    ?s rdfs:label ?label\n  }")
+            .unwrap()
+            .to_strings()
+            .unwrap();
+        assert_eq!(labels, vec!["x".to_string()]);
+        assert!(store.ask("ASK { ?s ?p ?o }").unwrap());
+        assert!(store.update("CLEAR ALL").is_err());
+        assert_eq!(store.executed().len(), 3);
+    }
+}
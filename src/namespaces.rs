@@ -4,6 +4,9 @@
 use {
     crate::{
         database_call,
+        PREFIX_DCTERMS,
+        PREFIX_FOAF,
+        PREFIX_SH,
         rdfox_api::{
             CPrefixes,
             CPrefixes_declarePrefix,
@@ -14,7 +17,14 @@ use {
     },
     ekg_namespace::{
         Class,
-        consts::{LOG_TARGET_DATABASE, PREFIX_OWL, PREFIX_RDF, PREFIX_RDFS, PREFIX_XSD},
+        consts::{
+            LOG_TARGET_DATABASE,
+            PREFIX_OWL,
+            PREFIX_RDF,
+            PREFIX_RDFS,
+            PREFIX_SKOS,
+            PREFIX_XSD,
+        },
         Namespace,
         Predicate,
     },
@@ -80,6 +90,20 @@ impl Namespaces {
         Ok(Arc::new(prefixes))
     }
 
+    /// Create namespaces from a prologue with prefix declarations in Turtle
+    /// syntax (`@prefix ex: <https://example.com/> .`) and/or SPARQL syntax
+    /// (`PREFIX ex: <https://example.com/>`), one declaration per line.
+    ///
+    /// Empty lines and `#`-comments are skipped, any other line that is not a
+    /// prefix declaration results in an error that identifies the line.
+    pub fn from_prologue(prologue: &str) -> Result<Arc<Self>, ekg_error::Error> {
+        let mut builder = Self::builder();
+        for (name, iri) in parse_prologue(prologue)? {
+            builder = builder.declare(Namespace::declare_from_str(name.as_str(), iri.as_str())?);
+        }
+        builder.build()
+    }
+
     /// Return the default namespaces: `RDF`, `RDFS`, `OWL` and `XSD`
     pub fn default_namespaces() -> Result<Arc<Self>, ekg_error::Error> {
         Self::empty()?
@@ -89,21 +113,35 @@ impl Namespaces {
             .add_namespace(PREFIX_XSD.deref())
     }
 
+    /// Return the commonly used W3C and community namespaces: the default
+    /// namespaces plus `dcterms`, `foaf`, `skos` and `sh`.
+    pub fn common() -> Result<Arc<Self>, ekg_error::Error> {
+        Self::default_namespaces()?
+            .add_namespace(PREFIX_DCTERMS.deref())?
+            .add_namespace(PREFIX_FOAF.deref())?
+            .add_namespace(PREFIX_SKOS.deref())?
+            .add_namespace(PREFIX_SH.deref())
+    }
+
     pub fn declare_namespace(
         self: &Arc<Self>,
         namespace: &Namespace,
     ) -> Result<NamespaceDeclareResult, ekg_error::Error> {
         tracing::trace!("Register namespace {namespace}");
-        if let Some(_already_registered) = self
+        if let Some(already_registered) = self
             .map
             .lock()
             .unwrap()
             .insert(namespace.name.clone(), namespace.clone())
         {
-            return Ok(NamespaceDeclareResult::PREFIXES_NO_CHANGE);
+            if already_registered.iri.as_str() == namespace.iri.as_str() {
+                return Ok(NamespaceDeclareResult::PREFIXES_NO_CHANGE);
+            }
+            // The prefix name now refers to another namespace IRI, so fall
+            // through to redeclare it on the C side as well
         }
-        let c_name = CString::new(namespace.name.as_str()).unwrap();
-        let c_iri = CString::new(namespace.iri.as_str()).unwrap();
+        let c_name = CString::new(namespace.name.as_str())?;
+        let c_iri = CString::new(namespace.iri.as_str())?;
         let mut result = NamespaceDeclareResult::PREFIXES_NO_CHANGE;
         database_call!(
             format!(
@@ -185,6 +223,60 @@ impl Namespaces {
         Ok(())
     }
 
+    /// Declare all namespaces of `other` in these namespaces as well.
+    ///
+    /// When both declare the same prefix name, the namespace of `other` wins.
+    pub fn merge(self: &Arc<Self>, other: &Namespaces) -> Result<Arc<Self>, ekg_error::Error> {
+        // Take a copy first, `other` might well be the same object as `self`
+        let namespaces = other
+            .map
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        for namespace in namespaces.iter() {
+            self.declare_namespace(namespace)?;
+        }
+        Ok(self.clone())
+    }
+
+    /// Iterate over a snapshot of all declared namespaces as pairs of prefix
+    /// name and namespace IRI, ordered by prefix name.
+    pub fn iter(&self) -> impl Iterator<Item = (String, String)> {
+        let mut pairs = self
+            .map
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, namespace)| (name.clone(), namespace.iri.as_str().to_string()))
+            .collect::<Vec<_>>();
+        pairs.sort();
+        pairs.into_iter()
+    }
+
+    /// Expand a compact IRI such as `ex:Person` into the full IRI, using the
+    /// declared namespace of its prefix name.
+    ///
+    /// Returns an error if the prefix name has not been declared.
+    pub fn expand_curie(&self, curie: &str) -> Result<String, ekg_error::Error> {
+        let Some((name, local_name)) = curie.split_once(':') else {
+            return Err(ekg_error::Error::Exception {
+                action:  format!("expanding {curie:?}"),
+                message: "a compact IRI has the form prefix:name".to_string(),
+            });
+        };
+        match self.map.lock().unwrap().get(format!("{name}:").as_str()) {
+            Some(namespace) => Ok(format!("{}{local_name}", namespace.iri.as_str())),
+            None => {
+                Err(ekg_error::Error::Exception {
+                    action:  format!("expanding {curie:?}"),
+                    message: format!("the prefix {name}: has not been declared"),
+                })
+            }
+        }
+    }
+
     pub fn c_ptr(&self) -> *const CPrefixes { self.inner }
 
     pub fn c_mut_ptr(&self) -> *mut CPrefixes { self.inner }
@@ -216,3 +308,62 @@ impl<'a> NamespacesBuilder {
         Ok(to_build)
     }
 }
+
+/// Parse the prefix declarations in the given prologue into pairs of prefix
+/// name (including the colon) and namespace IRI.
+fn parse_prologue(prologue: &str) -> Result<Vec<(String, String)>, ekg_error::Error> {
+    let re = fancy_regex::Regex::new(
+        r"^\s*(?:@prefix\s+([A-Za-z][\w.-]*)?:\s*<([^<>]*)>\s*\.|(?i:PREFIX)\s+([A-Za-z][\w.-]*)?:\s*<([^<>]*)>)\s*(?:#.*)?$",
+    )
+    .unwrap();
+    let mut declarations = Vec::new();
+    for (number, line) in prologue.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        match re.captures(line) {
+            Ok(Some(caps)) => {
+                let name = caps.get(1).or(caps.get(3)).map(|m| m.as_str()).unwrap_or("");
+                let iri = caps.get(2).or(caps.get(4)).map(|m| m.as_str()).unwrap_or("");
+                declarations.push((format!("{name}:"), iri.to_string()));
+            }
+            _ => {
+                return Err(ekg_error::Error::Exception {
+                    action:  "parsing prefix declarations".to_string(),
+                    message: format!(
+                        "line {} is not a prefix declaration: {trimmed}",
+                        number + 1
+                    ),
+                });
+            }
+        }
+    }
+    Ok(declarations)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test_log::test]
+    fn test_parse_prologue() {
+        let prologue = indoc::indoc! {r##"
+            @prefix ex: <https://example.com/> .
+            # a comment
+            PREFIX owl: <http://www.w3.org/2002/07/owl#>
+            prefix : <https://example.com/default#> # the default namespace
+            "##
+        };
+        let declarations = super::parse_prologue(prologue).unwrap();
+        assert_eq!(
+            declarations,
+            vec![
+                ("ex:".to_string(), "https://example.com/".to_string()),
+                ("owl:".to_string(), "http://www.w3.org/2002/07/owl#".to_string()),
+                (":".to_string(), "https://example.com/default#".to_string()),
+            ]
+        );
+        let err = super::parse_prologue("PREFIX ex: <https://example.com/>\n@prefix ex <x> .")
+            .unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+}
@@ -17,15 +17,17 @@ use {
     }
     ,
     std::{
+        collections::{BTreeMap, HashMap},
         ffi::CStr,
         fmt::{Display, Formatter},
         os::raw::c_char,
         path::Path,
         ptr,
-        sync::Arc,
+        sync::{Arc, Mutex},
     },
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FactDomain {
     ASSERTED,
     INFERRED,
@@ -48,25 +50,127 @@ impl Display for PersistenceMode {
     }
 }
 
+/// How a datastore treats `owl:sameAs`, see
+/// <https://docs.oxfordsemantic.tech/reasoning.html#equality>
+pub enum EqualityMode {
+    /// `owl:sameAs` has no special meaning
+    Off,
+    /// `owl:sameAs` is interpreted as equality, without the unique name
+    /// assumption
+    NoUNA,
+    /// `owl:sameAs` is interpreted as equality, with the unique name
+    /// assumption: deriving that two distinct IRIs are equal is a
+    /// contradiction
+    UNA,
+}
+
+impl Display for EqualityMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EqualityMode::Off => write!(f, "off"),
+            EqualityMode::NoUNA => write!(f, "noUNA"),
+            EqualityMode::UNA => write!(f, "UNA"),
+        }
+    }
+}
+
+/// What RDFox does when it encounters a malformed literal (such as
+/// `"abc"^^xsd:integer`) while importing data, see the
+/// `import.invalid-literal-policy` parameter in
+/// <https://docs.oxfordsemantic.tech/programmatic-access-APIs.html>
+pub enum ImportErrorMode {
+    /// Abort the import with an error (the RDFox default)
+    Abort,
+    /// Import the value as a plain string literal and report a warning
+    Warn,
+    /// Import the value as a plain string literal without reporting anything,
+    /// so nothing is skipped but the value loses its datatype silently
+    AsStringSilent,
+}
+
+impl Display for ImportErrorMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportErrorMode::Abort => write!(f, "error"),
+            ImportErrorMode::Warn => write!(f, "as-string"),
+            ImportErrorMode::AsStringSilent => write!(f, "as-string-silent"),
+        }
+    }
+}
+
+/// The type of a datastore (its `type` parameter), which determines how it
+/// stores resource IDs and can only be chosen when the datastore is created,
+/// see <https://docs.oxfordsemantic.tech/data-stores.html#data-store-types>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DataStoreType {
+    /// Narrow (32-bit) resource IDs and narrow triple indexes, using the
+    /// least memory but limited to about four billion resources and triples
     ParallelNN,
+    /// Narrow resource IDs with wide (64-bit) triple indexes, for more than
+    /// four billion triples over fewer resources
     ParallelNW,
+    /// Wide resource IDs and wide triple indexes, the RDFox default
     ParallelWW,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+impl DataStoreType {
+    /// All datastore types, in the order of their memory use
+    pub const ALL: [DataStoreType; 3] = [
+        DataStoreType::ParallelNN,
+        DataStoreType::ParallelNW,
+        DataStoreType::ParallelWW,
+    ];
+}
+
+impl Display for DataStoreType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataStoreType::ParallelNN => write!(f, "parallel-nn"),
+            DataStoreType::ParallelNW => write!(f, "parallel-nw"),
+            DataStoreType::ParallelWW => write!(f, "parallel-ww"),
+        }
+    }
+}
+
+/// A set of RDFox parameters, owning the underlying `CParameters` object.
+///
+/// `Parameters` is not `Clone` since copying it means allocating a fresh
+/// `CParameters` object, which can fail: use [`Parameters::try_clone`].
+#[derive(Debug)]
 pub struct Parameters {
     pub(crate) inner: Arc<*mut CParameters>,
+    /// A shadow copy of all the keys and values that have been set, since
+    /// the C API offers no way to enumerate them.
+    values: Mutex<BTreeMap<String, String>>,
+}
+
+impl PartialEq for Parameters {
+    fn eq(&self, other: &Self) -> bool {
+        *self.inner == *other.inner ||
+            *self.values.lock().unwrap() == *other.values.lock().unwrap()
+    }
 }
 
+impl Eq for Parameters {}
+
 unsafe impl Sync for Parameters {}
 
 unsafe impl Send for Parameters {}
 
 impl Display for Parameters {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Parameters[]") // TODO: show keys and values (currently not
-        // possible)
+        write!(f, "Parameters[")?;
+        for (index, (key, value)) in self.values.lock().unwrap().iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            if SENSITIVE_PARAMETERS.contains(&key.as_str()) {
+                write!(f, "{key}=***")?;
+            } else {
+                write!(f, "{key}={value}")?;
+            }
+        }
+        write!(f, "]")
     }
 }
 
@@ -86,45 +190,90 @@ impl Drop for Parameters {
 const SENSITIVE_PARAMETERS: [&str; 1] = ["license-content"];
 
 impl Parameters {
+    pub fn builder() -> ParametersBuilder { ParametersBuilder::default() }
+
     pub fn empty() -> Result<Self, ekg_error::Error> {
         let mut parameters: *mut CParameters = ptr::null_mut();
         database_call!(
             "Allocating parameters",
             CParameters_newEmptyParameters(&mut parameters)
         )?;
-        Ok(Parameters { inner: Arc::new(parameters) })
+        Ok(Parameters {
+            inner:  Arc::new(parameters),
+            values: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    /// Create parameters from the given keys and values, for instance loaded
+    /// from a configuration file.
+    ///
+    /// If RDFox rejects one of them, the returned error names the parameter
+    /// that could not be set.
+    pub fn from_map(map: &HashMap<String, String>) -> Result<Self, ekg_error::Error> {
+        let parameters = Self::empty()?;
+        for (key, value) in map.iter() {
+            parameters.set_string(key.as_str(), value.as_str())?;
+        }
+        Ok(parameters)
+    }
+
+    /// Create a new, independent, `CParameters` object with the same
+    /// parameters as this one.
+    pub fn try_clone(&self) -> Result<Self, ekg_error::Error> {
+        let parameters = Self::empty()?;
+        let values = self.values.lock().unwrap().clone();
+        for (key, value) in values.iter() {
+            parameters.set_string(key.as_str(), value.as_str())?;
+        }
+        Ok(parameters)
+    }
+
+    /// Set every parameter that has been set in `other` in these parameters
+    /// as well, overriding the value of a parameter that's set in both.
+    pub fn merge(self, other: &Parameters) -> Result<Self, ekg_error::Error> {
+        let values = other.values.lock().unwrap().clone();
+        for (key, value) in values.iter() {
+            self.set_string(key.as_str(), value.as_str())?;
+        }
+        Ok(self)
     }
 
     pub fn set_string(&self, key: &str, value: &str) -> Result<(), ekg_error::Error> {
-        let c_key = CString::new(key).unwrap();
-        let c_value = CString::new(value).unwrap();
-        let msg = if SENSITIVE_PARAMETERS.contains(&c_key.to_str().unwrap()) {
-            format!(
-                "Setting parameter {}=[***]",
-                c_key.to_str().unwrap(),
-            )
+        let c_key = CString::new(key)?;
+        let c_value = CString::new(value)?;
+        let msg = if SENSITIVE_PARAMETERS.contains(&key) {
+            format!("Setting parameter {key}=[***]")
         } else {
-            format!(
-                "Setting parameter {}=[{}]",
-                c_key.to_str().unwrap(),
-                c_value.to_str().unwrap()
-            )
+            format!("Setting parameter {key}=[{value}]")
         };
         database_call!(
             msg.as_str(),
             CParameters_setString(*self.inner, c_key.as_ptr(), c_value.as_ptr())
-        )
+        )?;
+        self.values
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_string());
+        Ok(())
     }
 
+    /// Return the value of the given parameter if it has been set, or `None`
+    /// if it hasn't (in which case RDFox uses its built-in default).
+    pub fn get(&self, key: &str) -> Result<Option<String>, ekg_error::Error> {
+        if !self.values.lock().unwrap().contains_key(key) {
+            return Ok(None);
+        }
+        self.get_string(key, "").map(Some)
+    }
+
+    /// Return all the parameters that have been set, ordered by key.
+    pub fn keys(&self) -> Vec<String> { self.values.lock().unwrap().keys().cloned().collect() }
+
     pub fn get_string(&self, key: &str, default: &str) -> Result<String, ekg_error::Error> {
-        let c_key = CString::new(key).unwrap();
-        let c_default = CString::new(default).unwrap();
+        let c_key = CString::new(key)?;
+        let c_default = CString::new(default)?;
         let mut c_value: *const c_char = ptr::null();
-        let msg = format!(
-            "Getting parameter {} with default value {}",
-            c_key.to_str().unwrap(),
-            c_default.to_str().unwrap()
-        );
+        let msg = format!("Getting parameter {key} with default value {default}");
         database_call!(
             msg.as_str(),
             CParameters_getString(
@@ -135,7 +284,7 @@ impl Parameters {
             )
         )?;
         let c_version = unsafe { CStr::from_ptr(c_value) };
-        Ok(c_version.to_str().unwrap().to_owned())
+        Ok(c_version.to_str()?.to_owned())
     }
 
     pub fn fact_domain(self, fact_domain: FactDomain) -> Result<Self, ekg_error::Error> {
@@ -147,6 +296,23 @@ impl Parameters {
         Ok(self)
     }
 
+    /// Confine the files that RDFox reads and writes on behalf of
+    /// statements and imports to the given directory and its
+    /// subdirectories, which is the recommended alternative to
+    /// [`switch_off_file_access_sandboxing`](Self::switch_off_file_access_sandboxing).
+    pub fn sandbox_directory(self, dir: &Path) -> Result<Self, ekg_error::Error> {
+        self.set_string("sandbox-directory", directory_as_str("sandbox-directory", dir)?)?;
+        Ok(self)
+    }
+
+    /// Allow RDFox to read and write any file that the process has access
+    /// to, by setting an empty sandbox directory.
+    ///
+    /// **This is a security risk**: any statement or import (think of
+    /// `LOAD <file:///etc/passwd>` coming in via user input) can then access
+    /// the whole filesystem. Only use this in development or when the
+    /// statements are fully trusted, prefer
+    /// [`sandbox_directory`](Self::sandbox_directory) otherwise.
     pub fn switch_off_file_access_sandboxing(self) -> Result<Self, ekg_error::Error> {
         self.set_string("sandbox-directory", "")?;
         Ok(self)
@@ -175,21 +341,49 @@ impl Parameters {
     }
 
     pub fn server_directory(self, dir: &Path) -> Result<Self, ekg_error::Error> {
-        if dir.is_dir() {
-            self.set_string("server-directory", dir.to_str().unwrap())?;
-            Ok(self)
-        } else {
-            panic!("{dir:?} is not a directory")
-        }
+        self.set_string("server-directory", directory_as_str("server-directory", dir)?)?;
+        Ok(self)
+    }
+
+    /// Persist the datastores (with [`PersistenceMode::File`]) in the given
+    /// server directory, which is created if it doesn't exist yet.
+    ///
+    /// This sets the server directory and the persistence mode together, so
+    /// that they always agree, and checks that the directory can be written
+    /// to, so that a bad directory is reported now rather than as an opaque
+    /// failure at the first commit.
+    pub fn persist_to_directory(self, dir: &Path) -> Result<Self, ekg_error::Error> {
+        std::fs::create_dir_all(dir).map_err(|err| {
+            invalid_parameter_path(
+                "server-directory",
+                dir,
+                format!("could not be created: {err}").as_str(),
+            )
+        })?;
+        let probe = dir.join(".rdfox-rs-write-test");
+        std::fs::write(&probe, b"")
+            .and_then(|_| std::fs::remove_file(&probe))
+            .map_err(|err| {
+                invalid_parameter_path(
+                    "server-directory",
+                    dir,
+                    format!("is not writable: {err}").as_str(),
+                )
+            })?;
+        self.server_directory(dir)?
+            .persist_datastore(PersistenceMode::File)
     }
 
     pub fn license_file(self, file: &Path) -> Result<Self, ekg_error::Error> {
-        if file.is_file() {
-            self.set_string("license-file", file.to_str().unwrap())?;
-            Ok(self)
-        } else {
-            panic!("{file:?} does not exist")
+        if !file.is_file() {
+            return Err(invalid_parameter_path(
+                "license-file",
+                file,
+                "does not exist",
+            ));
         }
+        self.set_string("license-file", path_as_str("license-file", file)?)?;
+        Ok(self)
     }
 
     pub fn license_content(self, content: &str) -> Result<Self, ekg_error::Error> {
@@ -221,6 +415,24 @@ impl Parameters {
         Ok(self)
     }
 
+    /// Set how malformed literals are handled when importing data, so that
+    /// messy real-world data can be bulk-loaded without aborting on the first
+    /// bad value.
+    ///
+    /// Note that the C API does not report how many values were affected.
+    pub fn import_error_handling(self, mode: ImportErrorMode) -> Result<Self, ekg_error::Error> {
+        self.set_string("import.invalid-literal-policy", &mode.to_string())?;
+        Ok(self)
+    }
+
+    /// If true, RDFox writes the plan that it chose for a query, with the
+    /// statistics it collected while evaluating it, to the query output.
+    /// The default value is false.
+    pub fn query_explain(self, on: bool) -> Result<Self, ekg_error::Error> {
+        self.set_string("query.explain", if on { "true" } else { "false" })?;
+        Ok(self)
+    }
+
     /// If true, all API calls are recorded in a script that
     /// the shell can replay later. later.
     /// The default value is false.
@@ -238,27 +450,204 @@ impl Parameters {
     pub fn api_log_directory(self, dir: &Path) -> Result<Self, ekg_error::Error> {
         if dir.exists() {
             let x = self.api_log(true)?;
-            x.set_string("api-log.directory", dir.to_str().unwrap())?;
+            x.set_string("api-log.directory", path_as_str("api-log.directory", dir)?)?;
             Ok(x)
         } else {
             tracing::error!(
                 "Could not enable logging since directory does not exist: {}",
-                dir.to_str().unwrap()
+                dir.display()
             );
             Ok(self)
         }
     }
 
-    pub fn data_store_type(self, data_store_type: DataStoreType) -> Result<Self, ekg_error::Error> {
-        match data_store_type {
-            DataStoreType::ParallelNN => self.set_string("type", "parallel-nn")?,
-            DataStoreType::ParallelNW => self.set_string("type", "parallel-nw")?,
-            DataStoreType::ParallelWW => self.set_string("type", "parallel-ww")?,
+    /// Set the number of threads that RDFox uses for importing data and for
+    /// reasoning (the `num-threads` server parameter).
+    ///
+    /// The number must be at least 1. To let RDFox decide (it uses the number
+    /// of logical processors by default), simply don't call this method.
+    pub fn number_of_threads(self, number_of_threads: usize) -> Result<Self, ekg_error::Error> {
+        if number_of_threads == 0 {
+            return Err(ekg_error::Error::Exception {
+                action:  "setting the number of threads".to_string(),
+                message: "the number of threads must be at least 1".to_string(),
+            });
         }
+        self.set_string(
+            "num-threads",
+            number_of_threads.to_string().as_str(),
+        )?;
+        Ok(self)
+    }
+
+    /// Set the way the datastore handles equality (`owl:sameAs`), which can
+    /// only be chosen when the datastore is created.
+    pub fn equality(self, mode: EqualityMode) -> Result<Self, ekg_error::Error> {
+        self.set_string("equality", &mode.to_string())?;
+        Ok(self)
+    }
+
+    /// Set the type of datastore to create, see [`DataStoreType`].
+    pub fn data_store_type(self, data_store_type: DataStoreType) -> Result<Self, ekg_error::Error> {
+        self.set_string("type", &data_store_type.to_string())?;
         Ok(self)
     }
 }
 
+/// The name of the parameter that sets the persistence mode of datastores
+#[cfg(feature = "rdfox-7-0")]
+const PERSISTENCE_PARAMETER: &str = "persistence";
+#[cfg(not(feature = "rdfox-7-0"))]
+const PERSISTENCE_PARAMETER: &str = "persist-ds";
+
+/// Collects parameter settings and checks them for consistency in
+/// [`build`](ParametersBuilder::build), so that conflicting settings are
+/// reported before a server or datastore is created with them.
+#[derive(Debug, Default)]
+pub struct ParametersBuilder {
+    values: BTreeMap<String, String>,
+    /// The first setting that was rejected, reported by `build`
+    error:  Option<ekg_error::Error>,
+}
+
+impl ParametersBuilder {
+    pub fn set(mut self, key: &str, value: &str) -> Self {
+        self.values.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn fact_domain(self, fact_domain: FactDomain) -> Self {
+        match fact_domain {
+            FactDomain::ASSERTED => self.set("fact-domain", "explicit"),
+            FactDomain::INFERRED => self.set("fact-domain", "derived"),
+            FactDomain::ALL => self.set("fact-domain", "all"),
+        }
+    }
+
+    pub fn persist_datastore(self, mode: PersistenceMode) -> Self {
+        self.set(PERSISTENCE_PARAMETER, &mode.to_string())
+    }
+
+    /// Set the server directory, which has to be an existing directory with
+    /// a UTF-8 path, just like for
+    /// [`Parameters::server_directory`], otherwise `build` fails.
+    pub fn server_directory(self, dir: &Path) -> Self { self.set_directory("server-directory", dir) }
+
+    /// Set the sandbox directory, which has to be an existing directory with
+    /// a UTF-8 path, just like for
+    /// [`Parameters::sandbox_directory`], otherwise `build` fails.
+    pub fn sandbox_directory(self, dir: &Path) -> Self { self.set_directory("sandbox-directory", dir) }
+
+    fn set_directory(mut self, key: &str, dir: &Path) -> Self {
+        match directory_as_str(key, dir) {
+            Ok(dir) => self.set(key, dir),
+            Err(err) => {
+                self.error.get_or_insert(err);
+                self
+            }
+        }
+    }
+
+    pub fn equality(self, mode: EqualityMode) -> Self { self.set("equality", &mode.to_string()) }
+
+    pub fn import_error_handling(self, mode: ImportErrorMode) -> Self {
+        self.set("import.invalid-literal-policy", &mode.to_string())
+    }
+
+    pub fn number_of_threads(self, number_of_threads: usize) -> Self {
+        self.set("num-threads", &number_of_threads.to_string())
+    }
+
+    pub fn data_store_type(self, data_store_type: DataStoreType) -> Self {
+        self.set("type", &data_store_type.to_string())
+    }
+
+    /// Check the collected settings for conflicts and create the
+    /// [`Parameters`] with them.
+    pub fn build(self) -> Result<Parameters, ekg_error::Error> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        self.validate()?;
+        let parameters = Parameters::empty()?;
+        for (key, value) in self.values.iter() {
+            parameters.set_string(key.as_str(), value.as_str())?;
+        }
+        Ok(parameters)
+    }
+
+    fn validate(&self) -> Result<(), ekg_error::Error> {
+        let persistence = self.values.get(PERSISTENCE_PARAMETER).map(String::as_str);
+        let server_directory = self.values.get("server-directory");
+        match (persistence, server_directory) {
+            (Some("off"), Some(_)) => {
+                return Err(conflicting_parameters(
+                    PERSISTENCE_PARAMETER,
+                    "server-directory",
+                    "a server directory is only used when persistence is switched on",
+                ));
+            }
+            (Some("file" | "file-sequence"), None) => {
+                return Err(conflicting_parameters(
+                    PERSISTENCE_PARAMETER,
+                    "server-directory",
+                    "persistence requires a server directory",
+                ));
+            }
+            _ => {}
+        }
+        if let Some(data_store_type) = self.values.get("type") {
+            if !DataStoreType::ALL
+                .iter()
+                .any(|known| known.to_string() == *data_store_type)
+            {
+                return Err(ekg_error::Error::Exception {
+                    action:  "building parameters".to_string(),
+                    message: format!(
+                        "unknown datastore type {data_store_type}, expected one of {}",
+                        DataStoreType::ALL.map(|known| known.to_string()).join(", ")
+                    ),
+                });
+            }
+        }
+        if self.values.get("num-threads").map(String::as_str) == Some("0") {
+            return Err(ekg_error::Error::Exception {
+                action:  "building parameters".to_string(),
+                message: "num-threads must be at least 1".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn conflicting_parameters(key1: &str, key2: &str, reason: &str) -> ekg_error::Error {
+    ekg_error::Error::Exception {
+        action:  "building parameters".to_string(),
+        message: format!("conflicting parameters {key1} and {key2}: {reason}"),
+    }
+}
+
+fn invalid_parameter_path(key: &str, path: &Path, problem: &str) -> ekg_error::Error {
+    ekg_error::Error::Exception {
+        action:  format!("setting parameter {key}"),
+        message: format!("{path:?} {problem}"),
+    }
+}
+
+fn path_as_str<'a>(key: &str, path: &'a Path) -> Result<&'a str, ekg_error::Error> {
+    path.to_str()
+        .ok_or_else(|| invalid_parameter_path(key, path, "is not valid UTF-8"))
+}
+
+/// Check that the given path is an existing directory and return it as the
+/// value of the given parameter.
+fn directory_as_str<'a>(key: &str, dir: &'a Path) -> Result<&'a str, ekg_error::Error> {
+    if !dir.is_dir() {
+        return Err(invalid_parameter_path(key, dir, "is not a directory"));
+    }
+    path_as_str(key, dir)
+}
+
 #[cfg(test)]
 mod tests {
     #[test_log::test]
@@ -267,5 +656,45 @@ mod tests {
         params.set_string("key1", "value1").unwrap();
         let value = params.get_string("key1", "whatever").unwrap();
         assert_eq!(value, "value1");
+        assert_eq!(params.get("key1").unwrap().as_deref(), Some("value1"));
+        assert_eq!(params.get("key2").unwrap(), None);
+        assert_eq!(params.to_string(), "Parameters[key1=value1]");
+    }
+
+    #[test_log::test]
+    fn test_clone_param() {
+        let params = crate::Parameters::empty().unwrap();
+        params.set_string("key1", "value1").unwrap();
+        let clone = params.try_clone().unwrap();
+        assert_ne!(*params.inner, *clone.inner);
+        drop(params);
+        assert_eq!(clone.get_string("key1", "whatever").unwrap(), "value1");
+    }
+
+    #[test_log::test]
+    fn test_builder_conflicts() {
+        use crate::PersistenceMode;
+        let result = crate::Parameters::builder()
+            .persist_datastore(PersistenceMode::Off)
+            .server_directory(std::path::Path::new("/tmp"))
+            .build();
+        assert!(result.is_err());
+        let result = crate::Parameters::builder()
+            .persist_datastore(PersistenceMode::File)
+            .build();
+        assert!(result.is_err());
+        let result = crate::Parameters::builder().set("type", "seq").build();
+        assert!(result.is_err());
+    }
+
+    #[test_log::test]
+    fn test_invalid_parameter_path() {
+        let params = crate::Parameters::empty().unwrap();
+        let result = params.server_directory(std::path::Path::new("/does/not/exist"));
+        assert!(result.is_err());
+        let result = crate::Parameters::builder()
+            .sandbox_directory(std::path::Path::new("/does/not/exist"))
+            .build();
+        assert!(result.is_err());
     }
 }
@@ -0,0 +1,37 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+//! The types that most applications need, to be imported in one go:
+//!
+//! ```no_run
+//! use rdfox_rs::prelude::*;
+//! ```
+//!
+//! This is deliberately a small selection: starting a server, connecting to
+//! a datastore, running statements in transactions and reading the answers.
+//! Everything else is available from the crate root.
+
+pub use crate::{
+    Cursor,
+    CursorRow,
+    DataStore,
+    DataStoreConnection,
+    FactDomain,
+    GraphConnection,
+    GraphTarget,
+    Namespaces,
+    OpenedCursor,
+    Parameters,
+    RoleCreds,
+    Server,
+    ServerConnection,
+    Statement,
+    Term,
+    Transaction,
+    APPLICATION_N_QUADS,
+    APPLICATION_N_TRIPLES,
+    APPLICATION_TRIG,
+    SPARQL_RESULTS_CSV,
+    SPARQL_RESULTS_TSV,
+};
+pub use ekg_namespace::consts::TEXT_TURTLE;
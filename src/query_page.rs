@@ -0,0 +1,16 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use crate::ResultSet;
+
+/// One page of the answers of a query, see
+/// [`DataStoreConnection::query_page`](crate::DataStoreConnection::query_page).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryPage {
+    /// The answers on this page, at most the requested number
+    pub result_set: ResultSet,
+    /// The number of answers that precede this page
+    pub offset:     u64,
+    /// True if there is at least one answer after this page
+    pub has_more:   bool,
+}
@@ -0,0 +1,52 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    crate::{DataStoreConnection, ResultSet, UpdateReport},
+    std::sync::Arc,
+};
+
+/// The operations that application code typically performs on a datastore,
+/// as a trait so that the code can be unit-tested against a
+/// [`MockStore`](crate::MockStore) (with the `testing` feature) instead of a
+/// running RDFox server.
+///
+/// Note that this does not remove the native dependency: the `testing`
+/// feature only adds `MockStore`, this crate still links the RDFox library
+/// (which `build.rs` downloads), so a test crate that uses `MockStore` still
+/// needs that library to build, even though it needs no server or license.
+///
+/// The statements are plain SPARQL text, including any `PREFIX`
+/// declarations, since building a [`Statement`](crate::Statement) already
+/// requires RDFox.
+///
+/// The trait only covers these three calls: for a [`DataStoreConnection`]
+/// each of them is the inherent method of the same name, evaluated with the
+/// [`prefixes`](DataStoreConnection::prefixes) of the connection, and
+/// everything else (imports, exports, transactions, cursors) is only
+/// available on the connection itself.
+pub trait RdfStore {
+    /// Evaluate a SELECT query and return all its answers.
+    fn select(&self, sparql: &str) -> Result<ResultSet, ekg_error::Error>;
+
+    /// Evaluate an ASK query.
+    fn ask(&self, sparql: &str) -> Result<bool, ekg_error::Error>;
+
+    /// Evaluate an update in a read/write transaction of its own, or in the
+    /// transaction that is already active.
+    fn update(&self, sparql: &str) -> Result<UpdateReport, ekg_error::Error>;
+}
+
+impl RdfStore for Arc<DataStoreConnection> {
+    fn select(&self, sparql: &str) -> Result<ResultSet, ekg_error::Error> {
+        DataStoreConnection::select(self, &self.prefixes()?, sparql)
+    }
+
+    fn ask(&self, sparql: &str) -> Result<bool, ekg_error::Error> {
+        DataStoreConnection::ask(self, &self.prefixes()?, sparql)
+    }
+
+    fn update(&self, sparql: &str) -> Result<UpdateReport, ekg_error::Error> {
+        DataStoreConnection::update(self, &self.prefixes()?, sparql)
+    }
+}
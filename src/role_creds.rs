@@ -4,12 +4,32 @@
 const RDFOX_DEFAULT_ROLE_USERID: &str = "admin";
 const RDFOX_DEFAULT_ROLE_PASSWD: &str = "admin";
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// The name and password of an RDFox role.
+///
+/// The password is never shown by `Debug` and is overwritten with zeroes
+/// when the credentials are dropped.
+#[derive(Clone, PartialEq, Eq)]
 pub struct RoleCreds {
     pub(crate) role_name: String,
     pub(crate) password:  String,
 }
 
+impl std::fmt::Debug for RoleCreds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RoleCreds")
+            .field("role_name", &self.role_name)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Drop for RoleCreds {
+    fn drop(&mut self) {
+        // Safety: zero bytes are valid UTF-8
+        zeroize_bytes(unsafe { self.password.as_bytes_mut() });
+    }
+}
+
 impl Default for RoleCreds {
     fn default() -> Self {
         Self {
@@ -28,3 +48,29 @@ impl RoleCreds {
         }
     }
 }
+
+/// Overwrite the bytes of a `CString` that held a password with zeroes
+/// before its memory is released, so that the password doesn't linger on the
+/// heap.
+pub(crate) fn zeroize_c_string(c_string: std::ffi::CString) {
+    zeroize_bytes(c_string.into_bytes().as_mut_slice());
+}
+
+fn zeroize_bytes(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        // A volatile write prevents the compiler from optimizing this away
+        unsafe { std::ptr::write_volatile(byte, 0) };
+    }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    #[test_log::test]
+    fn test_debug_redacts_password() {
+        let creds = super::RoleCreds::new("someone", "secret");
+        let debug = format!("{creds:?}");
+        assert!(debug.contains("someone"));
+        assert!(!debug.contains("secret"));
+    }
+}
@@ -4,7 +4,9 @@
 use {
     crate::{
         database_call,
+        exception::version_mismatch_error,
         Parameters,
+        PersistenceMode,
         rdfox_api::{
             CServer_createFirstLocalServerRole,
             CServer_getNumberOfLocalServerRoles,
@@ -13,12 +15,14 @@ use {
             CServerConnection,
             CServerConnection_newServerConnection,
         },
+        role_creds::zeroize_c_string,
         RoleCreds,
         server_connection::ServerConnection,
     },
     ekg_namespace::consts::LOG_TARGET_DATABASE,
     std::{
         ffi::CString,
+        path::Path,
         ptr,
         sync::{
             Arc,
@@ -27,6 +31,31 @@ use {
     },
 };
 
+/// The version of RDFox that the bindings were generated for, the one given
+/// by the `RDFOX_VERSION_EXPECTED` environment variable at build time or the
+/// one selected with the `rdfox-X-Y` features.
+pub const RDFOX_VERSION_EXPECTED: &str = env!("RDFOX_VERSION_EXPECTED");
+
+/// How far the version of the loaded RDFox library may diverge from
+/// [`RDFOX_VERSION_EXPECTED`], see [`Server::check_version`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionTolerance {
+    /// The versions have to be the same, where a missing trailing number
+    /// counts as zero (so 7.0 is the same as 7.0.0)
+    Exact,
+    /// The major and minor versions (such as 6.3b) have to be the same
+    Minor,
+    /// Only the major versions have to be the same
+    Major,
+}
+
+/// The tolerance that [`Server::start_with_parameters`] checks the version
+/// of the loaded RDFox library with, if at all.
+#[cfg(feature = "rdfox-version-check")]
+const STARTUP_VERSION_TOLERANCE: Option<VersionTolerance> = Some(VersionTolerance::Minor);
+#[cfg(not(feature = "rdfox-version-check"))]
+const STARTUP_VERSION_TOLERANCE: Option<VersionTolerance> = None;
+
 #[derive(Debug)]
 pub struct Server {
     default_role_creds: RoleCreds,
@@ -51,9 +80,41 @@ impl Server {
         Self::start_with_parameters(role_creds, None)
     }
 
+    /// Start a local in-process RDFox server with sensible defaults: the
+    /// given license file, the default role, no persistence (everything is
+    /// kept in memory only) and as many threads as RDFox sees fit.
+    ///
+    /// Use [`Server::start_with_parameters`] for full control.
+    pub fn start_local(license: &Path) -> Result<Arc<Self>, ekg_error::Error> {
+        let params = Parameters::empty()?
+            .license_file(license)?
+            .persist_datastore(PersistenceMode::Off)?;
+        #[cfg(not(feature = "rdfox-7-0"))]
+        let params = params.persist_roles(PersistenceMode::Off)?;
+        Self::start_with_parameters(RoleCreds::default(), Some(params))
+    }
+
+    /// Start a local in-process RDFox server with the given parameters.
+    ///
+    /// With the `rdfox-version-check` feature the version of the loaded
+    /// RDFox library is checked with [`VersionTolerance::Minor`], see
+    /// [`start_with_version_tolerance`](Self::start_with_version_tolerance)
+    /// to choose the tolerance.
     pub fn start_with_parameters(
         role_creds: RoleCreds,
         params: Option<Parameters>,
+    ) -> Result<Arc<Self>, ekg_error::Error> {
+        Self::start_with_version_tolerance(role_creds, params, STARTUP_VERSION_TOLERANCE)
+    }
+
+    /// Same as [`start_with_parameters`](Self::start_with_parameters) but
+    /// checking the version of the loaded RDFox library with the given
+    /// tolerance, if any, see [`check_version`](Self::check_version). The
+    /// server is stopped again when the check fails.
+    pub fn start_with_version_tolerance(
+        role_creds: RoleCreds,
+        params: Option<Parameters>,
+        tolerance: Option<VersionTolerance>,
     ) -> Result<Arc<Self>, ekg_error::Error> {
         if let Some(params) = params {
             #[cfg(feature = "rdfox-7-0")]
@@ -98,20 +159,26 @@ impl Server {
             target: LOG_TARGET_DATABASE,
             "Local RDFox server has been started"
         );
-        Ok(Arc::new(server))
+        let server = Arc::new(server);
+        if let Some(tolerance) = tolerance {
+            server.check_version(tolerance)?;
+        }
+        Ok(server)
     }
 
     pub fn create_role(&self, role_creds: &RoleCreds) -> Result<(), ekg_error::Error> {
-        let c_role_name = CString::new(role_creds.role_name.as_str()).unwrap();
-        let c_password = CString::new(role_creds.password.as_str()).unwrap();
+        let c_role_name = CString::new(role_creds.role_name.as_str())?;
+        let c_password = CString::new(role_creds.password.as_str())?;
         let msg = format!(
             "Creating server role named [{}]",
             role_creds.role_name
         );
-        database_call!(
+        let result = database_call!(
             msg.as_str(),
             CServer_createFirstLocalServerRole(c_role_name.as_ptr(), c_password.as_ptr())
-        )
+        );
+        zeroize_c_string(c_password);
+        result
     }
 
     pub fn get_number_of_local_server_roles(&self) -> Result<u16, ekg_error::Error> {
@@ -134,17 +201,19 @@ impl Server {
         self: &Arc<Self>,
         role_creds: RoleCreds,
     ) -> Result<Arc<ServerConnection>, ekg_error::Error> {
-        let c_role_name = CString::new(role_creds.role_name.as_str()).unwrap();
-        let c_password = CString::new(role_creds.password.as_str()).unwrap();
+        let c_role_name = CString::new(role_creds.role_name.as_str())?;
+        let c_password = CString::new(role_creds.password.as_str())?;
         let mut server_connection_ptr: *mut CServerConnection = ptr::null_mut();
-        database_call!(
+        let result = database_call!(
             "Creating a server connection",
             CServerConnection_newServerConnection(
                 c_role_name.as_ptr(),
                 c_password.as_ptr(),
                 &mut server_connection_ptr,
             )
-        )?;
+        );
+        zeroize_c_string(c_password);
+        result?;
         if server_connection_ptr.is_null() {
             tracing::error!(
                 target: LOG_TARGET_DATABASE,
@@ -159,6 +228,36 @@ impl Server {
         )))
     }
 
+    /// Return the version of the RDFox library that is actually linked,
+    /// which can differ from the version that was expected at build time
+    /// (`RDFOX_VERSION_EXPECTED`) when a different dylib is deployed.
+    pub fn version(self: &Arc<Self>) -> Result<String, ekg_error::Error> {
+        self.connection_with_default_role()?.get_version()
+    }
+
+    /// Return an error if the version of the loaded RDFox library diverges
+    /// from the version that the bindings were generated for
+    /// ([`RDFOX_VERSION_EXPECTED`]) by more than the given tolerance, since
+    /// the C API of another version may not match the bindings.
+    ///
+    /// The error can be recognized with [`is_version_mismatch`](crate::is_version_mismatch).
+    /// See [`Server::start_with_version_tolerance`] to check this on startup.
+    pub fn check_version(self: &Arc<Self>, tolerance: VersionTolerance) -> Result<(), ekg_error::Error> {
+        let linked = self.version()?;
+        if version_matches(RDFOX_VERSION_EXPECTED, linked.as_str(), tolerance) {
+            return Ok(());
+        }
+        tracing::error!(
+            target: LOG_TARGET_DATABASE,
+            "RDFox {linked} is loaded but the bindings are for RDFox {RDFOX_VERSION_EXPECTED}"
+        );
+        Err(version_mismatch_error(
+            RDFOX_VERSION_EXPECTED,
+            linked.as_str(),
+            tolerance,
+        ))
+    }
+
     pub fn stop(&mut self) {
         *self.running.get_mut() = false;
         tracing::trace!(
@@ -176,3 +275,40 @@ impl Server {
         );
     }
 }
+
+/// Compare two RDFox versions, such as `6.3a` and `7.0.1`, number by number,
+/// up to what the given tolerance requires.
+fn version_matches(expected: &str, linked: &str, tolerance: VersionTolerance) -> bool {
+    let split = |version: &str| {
+        version
+            .trim()
+            .split('.')
+            .map(|part| part.to_string())
+            .collect::<Vec<_>>()
+    };
+    let (expected, linked) = (split(expected), split(linked));
+    let number_of_parts = match tolerance {
+        VersionTolerance::Major => 1,
+        VersionTolerance::Minor => 2,
+        VersionTolerance::Exact => expected.len().max(linked.len()),
+    };
+    (0..number_of_parts).all(|index| {
+        let part = |parts: &Vec<String>| parts.get(index).cloned().unwrap_or_else(|| "0".to_string());
+        part(&expected) == part(&linked)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{version_matches, VersionTolerance};
+
+    #[test_log::test]
+    fn test_version_matches() {
+        assert!(version_matches("7.0", "7.0.0", VersionTolerance::Exact));
+        assert!(!version_matches("7.0", "7.0.1", VersionTolerance::Exact));
+        assert!(version_matches("7.0", "7.0.1", VersionTolerance::Minor));
+        assert!(!version_matches("6.3a", "6.3b", VersionTolerance::Minor));
+        assert!(version_matches("6.3a", "6.2", VersionTolerance::Major));
+        assert!(!version_matches("6.3a", "7.0", VersionTolerance::Major));
+    }
+}
@@ -5,21 +5,26 @@ use {
         DataStoreConnection,
         rdfox_api::{
             CServerConnection,
+            CServerConnection_changeRolePassword,
             CServerConnection_createDataStore,
             CServerConnection_deleteDataStore,
             CServerConnection_destroy,
             CServerConnection_getMemoryUse,
             CServerConnection_getNumberOfThreads,
             CServerConnection_getVersion,
+            CServerConnection_listDataStores,
             CServerConnection_newDataStoreConnection,
             CServerConnection_setNumberOfThreads,
         },
+        Parameters,
+        role_creds::zeroize_c_string,
         RoleCreds,
         Server,
     },
     ekg_namespace::consts::LOG_TARGET_DATABASE,
     std::{
-        ffi::{CStr, CString},
+        ffi::{c_void, CStr, CString},
+        os::raw::c_char,
         ptr,
         sync::Arc,
     },
@@ -28,7 +33,6 @@ use {
 /// A connection to a given [`Server`].
 #[derive(Debug)]
 pub struct ServerConnection {
-    #[allow(dead_code)]
     role_creds: RoleCreds,
     server: Arc<Server>,
     inner: *mut CServerConnection,
@@ -77,6 +81,25 @@ impl ServerConnection {
         connection
     }
 
+    /// Establish a new connection to the same server with the same role, to
+    /// replace this one when it's no longer usable (see
+    /// [`is_connection_lost`](crate::is_connection_lost)).
+    ///
+    /// Connections are shared, so this one is left as it is, it's up to the
+    /// caller to use the returned connection from now on.
+    pub fn reconnect(&self) -> Result<Arc<ServerConnection>, ekg_error::Error> {
+        if !self.server.is_running() {
+            tracing::error!(
+                target: LOG_TARGET_DATABASE,
+                "Cannot reconnect, {} is not running",
+                self.server
+            );
+            return Err(ekg_error::Error::CouldNotConnectToServer);
+        }
+        tracing::info!(target: LOG_TARGET_DATABASE, "Reconnecting to {}", self.server);
+        self.server.connection(self.role_creds.clone())
+    }
+
     /// Return the version number of the underlying database engine
     ///
     /// CRDFOX const CException*
@@ -91,7 +114,7 @@ impl ServerConnection {
             CServerConnection_getVersion(self.inner, &mut c_buf)
         )?;
         let c_version = unsafe { CStr::from_ptr(c_buf) };
-        Ok(c_version.to_str().unwrap().to_owned())
+        Ok(c_version.to_str()?.to_owned())
     }
 
     pub fn get_number_of_threads(&self) -> Result<u32, ekg_error::Error> {
@@ -144,7 +167,7 @@ impl ServerConnection {
     ) -> Result<(), ekg_error::Error> {
         assert!(!self.inner.is_null());
         let msg = format!("Deleting {data_store}");
-        let c_name = CString::new(data_store.name.as_str()).unwrap();
+        let c_name = CString::new(data_store.name.as_str())?;
         database_call!(
             msg.as_str(),
             CServerConnection_deleteDataStore(self.inner, c_name.as_ptr())
@@ -160,7 +183,7 @@ impl ServerConnection {
             "Creating {data_store:}"
         );
         assert!(!self.inner.is_null());
-        let c_name = CString::new(data_store.name.as_str()).unwrap();
+        let c_name = CString::new(data_store.name.as_str())?;
         database_call!(
             "creating a datastore",
             CServerConnection_createDataStore(
@@ -176,6 +199,88 @@ impl ServerConnection {
         Ok(())
     }
 
+    /// Return the names of all datastores in the server.
+    pub fn list_data_stores(&self) -> Result<Vec<String>, ekg_error::Error> {
+        assert!(!self.inner.is_null());
+        let mut names: Vec<String> = Vec::new();
+        database_call!(
+            "Listing the datastores",
+            CServerConnection_listDataStores(
+                self.inner,
+                &mut names as *mut Vec<String> as *mut c_void,
+                Some(Self::collect_data_store_name),
+            )
+        )?;
+        Ok(names)
+    }
+
+    extern "C" fn collect_data_store_name(context: *mut c_void, data_store_name: *const c_char) {
+        let names = unsafe { &mut *(context as *mut Vec<String>) };
+        let name = unsafe { CStr::from_ptr(data_store_name) };
+        names.push(name.to_string_lossy().into_owned());
+    }
+
+    /// Create a new datastore with the given name and parameters and return
+    /// a connection to it.
+    ///
+    /// Returns an error saying that the datastore already exists, rather
+    /// than whatever RDFox reports, if there is one with the same name.
+    pub fn create_data_store_named(
+        self: &Arc<Self>,
+        name: &str,
+        parameters: &Parameters,
+    ) -> Result<Arc<DataStoreConnection>, ekg_error::Error> {
+        if self.list_data_stores()?.iter().any(|existing| existing == name) {
+            return Err(ekg_error::Error::Exception {
+                action:  format!("creating datastore {name}"),
+                message: format!("datastore {name} already exists"),
+            });
+        }
+        let data_store = DataStore::declare_with_parameters(name, parameters.try_clone()?)?;
+        self.create_data_store(&data_store)?;
+        self.connect_to_data_store(&data_store)
+    }
+
+    /// Delete the datastore with the given name.
+    pub fn delete_data_store_named(&self, name: &str) -> Result<(), ekg_error::Error> {
+        let data_store = DataStore::declare_with_parameters(name, Parameters::empty()?)?;
+        self.delete_data_store(&data_store)
+    }
+
+    /// Change the password of the given role, for instance to rotate
+    /// secrets. This requires a connection with sufficient privileges to
+    /// manage that role.
+    pub fn set_role_password(&self, role: &str, new_password: &str) -> Result<(), ekg_error::Error> {
+        assert!(!self.inner.is_null());
+        let c_role_name = CString::new(role)?;
+        let c_password = CString::new(new_password)?;
+        let result = database_call!(
+            format!("Changing the password of role [{role}]").as_str(),
+            CServerConnection_changeRolePassword(
+                self.inner,
+                c_role_name.as_ptr(),
+                c_password.as_ptr(),
+            )
+        );
+        zeroize_c_string(c_password);
+        result
+    }
+
+    /// Check the given credentials against the server by trying to open a
+    /// connection with them. Returns `Ok(false)` if the server rejects them
+    /// and an error for any other failure.
+    pub fn verify_credentials(&self, role_creds: &RoleCreds) -> Result<bool, ekg_error::Error> {
+        match self.server.connection(role_creds.clone()) {
+            Ok(_) => Ok(true),
+            Err(ekg_error::Error::Exception { message, .. })
+                if message.contains("AuthenticationException") =>
+            {
+                Ok(false)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
     pub fn connect_to_data_store(
         self: &Arc<Self>,
         data_store: &Arc<DataStore>,
@@ -187,7 +292,7 @@ impl ServerConnection {
         );
         assert!(!self.inner.is_null());
         let mut ds_connection = DataStoreConnection::new(self, data_store, ptr::null_mut());
-        let c_name = CString::new(data_store.name.as_str()).unwrap();
+        let c_name = CString::new(data_store.name.as_str())?;
         tracing::debug!(
             target: LOG_TARGET_DATABASE,
             conn = ds_connection.number,
@@ -3,17 +3,37 @@
 
 use {
     core::fmt::{Display, Formatter},
-    crate::{Cursor, DataStoreConnection, Namespaces, Parameters},
-    ekg_namespace::consts::{DEFAULT_GRAPH_RDFOX, LOG_TARGET_SPARQL},
+    crate::{Cursor, DataStoreConnection, Namespaces, Parameters, Term},
+    ekg_namespace::{
+        consts::{DEFAULT_GRAPH_RDFOX, LOG_TARGET_SPARQL},
+        Graph,
+    },
     indoc::formatdoc,
-    std::{borrow::Cow, ffi::CString, ops::Deref, sync::Arc},
+    std::{borrow::Cow, ffi::CString, ops::Deref, sync::Arc, time::Duration},
 };
 
+/// The kind of a SPARQL statement, which determines what evaluating it
+/// produces: a boolean, a result set, triples or changes to the datastore.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum QueryType {
+    Ask,
+    Select,
+    Construct,
+    Describe,
+    Update,
+}
+
 /// SPARQL Statement
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Statement {
     pub prefixes: Arc<Namespaces>,
     pub(crate) text: String,
+    /// The time after which the evaluation is interrupted, if any
+    pub(crate) timeout: Option<Duration>,
+    /// The number of `FROM` and `FROM NAMED` clauses that were added with
+    /// [`from_graph`](Statement::from_graph) and
+    /// [`from_named`](Statement::from_named)
+    dataset_clauses: usize,
 }
 
 impl Display for Statement {
@@ -31,11 +51,158 @@ impl Statement {
         let s = Self {
             prefixes: prefixes.clone(),
             text: format!("{}\n{}", &prefixes.to_string(), statement.trim()),
+            timeout: None,
+            dataset_clauses: 0,
         };
         tracing::trace!(target: LOG_TARGET_SPARQL, "{:}", s);
         Ok(s)
     }
 
+    /// Replace every occurrence of the parameter `?$name` in the statement
+    /// with the given term, rendered in SPARQL syntax with all necessary
+    /// escaping, so that values coming from user input can be put into a
+    /// statement safely.
+    ///
+    /// Returns an error if the statement does not contain the parameter.
+    pub fn bind(mut self, name: &str, value: &Term) -> Result<Self, ekg_error::Error> {
+        let placeholder = format!("?${name}");
+        let value = value.to_string();
+        let mut text = String::with_capacity(self.text.len());
+        let mut rest = self.text.as_str();
+        let mut found = false;
+        while let Some(pos) = rest.find(placeholder.as_str()) {
+            let after = &rest[pos + placeholder.len()..];
+            text.push_str(&rest[..pos]);
+            if after.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+                // This is another parameter that happens to start with the same name
+                text.push_str(placeholder.as_str());
+            } else {
+                text.push_str(value.as_str());
+                found = true;
+            }
+            rest = after;
+        }
+        text.push_str(rest);
+        if !found {
+            return Err(ekg_error::Error::Exception {
+                action:  "binding a statement parameter".to_string(),
+                message: format!("statement has no parameter {placeholder}"),
+            });
+        }
+        self.text = text;
+        tracing::trace!(target: LOG_TARGET_SPARQL, "{:}", self);
+        Ok(self)
+    }
+
+    /// Restrict the default graph of the query to (the merge of) the given
+    /// graph and any other graph given this way, by adding a `FROM` clause.
+    ///
+    /// Returns an error for an update or when the statement text already
+    /// specifies a dataset itself.
+    pub fn from_graph(&mut self, graph: &Graph) -> Result<&mut Self, ekg_error::Error> {
+        self.add_dataset_clause(format!("FROM {}", graph.as_display_iri()))
+    }
+
+    /// Make the given graph available to `GRAPH` patterns of the query, by
+    /// adding a `FROM NAMED` clause, see [`from_graph`](Self::from_graph).
+    pub fn from_named(&mut self, graph: &Graph) -> Result<&mut Self, ekg_error::Error> {
+        self.add_dataset_clause(format!(
+            "FROM NAMED {}",
+            graph.as_display_iri()
+        ))
+    }
+
+    fn add_dataset_clause(&mut self, clause: String) -> Result<&mut Self, ekg_error::Error> {
+        let error = |message: &str| {
+            ekg_error::Error::Exception {
+                action:  format!("adding {clause} to a statement"),
+                message: message.to_string(),
+            }
+        };
+        if self.query_type()? == QueryType::Update {
+            return Err(error("an update has no dataset clauses"));
+        }
+        let Some(position) = find_top_level_keyword(self.text.as_str(), "WHERE")
+            .or_else(|| self.text.find('{'))
+        else {
+            return Err(error("the statement has no WHERE clause"));
+        };
+        let number_of_from_clauses = keyword_positions(&self.text[..position], "FROM").len();
+        if number_of_from_clauses > self.dataset_clauses {
+            return Err(error(
+                "the statement already specifies a dataset with FROM or FROM NAMED",
+            ));
+        }
+        self.text.insert_str(position, format!("{clause}\n").as_str());
+        self.dataset_clauses += 1;
+        tracing::trace!(target: LOG_TARGET_SPARQL, "{:}", self);
+        Ok(self)
+    }
+
+    /// Return a copy of this query that skips the first `offset` answers and
+    /// returns at most `limit` answers, by appending `OFFSET` and `LIMIT`.
+    ///
+    /// Returns an error for an `ASK` query, an update, or a query that
+    /// already has an `OFFSET` or `LIMIT` of its own (outside of any
+    /// subquery).
+    pub fn page(&self, offset: u64, limit: u64) -> Result<Self, ekg_error::Error> {
+        let error = |message: &str| {
+            ekg_error::Error::Exception {
+                action:  format!("paging a statement with OFFSET {offset} LIMIT {limit}"),
+                message: message.to_string(),
+            }
+        };
+        if matches!(self.query_type()?, QueryType::Ask | QueryType::Update) {
+            return Err(error("only the answers of a query can be paged"));
+        }
+        if find_top_level_keyword(self.text.as_str(), "OFFSET").is_some() ||
+            find_top_level_keyword(self.text.as_str(), "LIMIT").is_some()
+        {
+            return Err(error("the statement already has an OFFSET or LIMIT"));
+        }
+        let mut page = self.clone();
+        page.text = format!("{}\nOFFSET {offset}\nLIMIT {limit}", self.text.trim_end());
+        tracing::trace!(target: LOG_TARGET_SPARQL, "{:}", page);
+        Ok(page)
+    }
+
+    /// Return true if the query orders its answers with `ORDER BY` (outside
+    /// of any subquery), without which the answers of consecutive pages (see
+    /// [`page`](Self::page)) may overlap or miss answers.
+    pub fn has_order_by(&self) -> bool {
+        find_top_level_keyword(self.text.as_str(), "ORDER").is_some()
+    }
+
+    /// Interrupt the evaluation of this statement (via a cursor or a
+    /// [`Streamer`](crate::Streamer)) when it takes longer than the given
+    /// time, in which case the evaluation fails with an error for which
+    /// [`is_timeout`](crate::is_timeout) is true.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn timeout(&self) -> Option<Duration> { self.timeout }
+
+    /// Start building a SELECT query that projects the given variables
+    /// (with or without their leading `?`), or all variables (`*`) if none
+    /// are given.
+    pub fn select(prefixes: &Arc<Namespaces>, variables: &[&str]) -> SelectBuilder {
+        SelectBuilder {
+            prefixes: prefixes.clone(),
+            distinct: false,
+            variables: variables
+                .iter()
+                .map(|variable| format!("?{}", variable.trim_start_matches(['?', '$'])))
+                .collect(),
+            dataset: Vec::new(),
+            patterns: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            offset: None,
+        }
+    }
+
     pub fn cursor(
         &self,
         connection: &Arc<DataStoreConnection>,
@@ -44,16 +211,41 @@ impl Statement {
         Cursor::create(connection, parameters, self)
     }
 
+    /// Check that the statement is a syntactically valid query for the given
+    /// connection without evaluating it.
+    ///
+    /// RDFox has no parse-only API, so this compiles the query into a cursor
+    /// that is never opened (and hence never touches any data) and then
+    /// drops it. A syntax error is returned as an
+    /// [`ekg_error::Error::Exception`] whose message is the one that RDFox
+    /// produced, including the line and column of the error.
+    ///
+    /// Note that update statements cannot be validated this way.
+    pub fn validate(&self, connection: &Arc<DataStoreConnection>) -> Result<(), ekg_error::Error> {
+        self.cursor(connection, &Parameters::empty()?)
+            .map(|_cursor| ())
+    }
+
     pub(crate) fn as_c_string(&self) -> Result<CString, ekg_error::Error> {
         Ok(CString::new(self.text.as_str())?)
     }
 
     pub fn as_str(&self) -> &str { self.text.as_str() }
 
+    /// Return the kind of this statement, based on the first keyword after
+    /// the prologue (the `PREFIX` and `BASE` declarations).
+    pub fn query_type(&self) -> Result<QueryType, ekg_error::Error> {
+        query_type_of(self.text.as_str())
+    }
+
     pub fn no_comments(&self) -> String { no_comments(self.text.as_str()) }
 
     /// Return a Statement that can be used to export all data in
-    /// `application/nquads` format
+    /// `application/nquads` format.
+    ///
+    /// Triples in named graphs keep their graph name as the fourth column,
+    /// only the triples in the default graph get the RDFox default graph
+    /// IRI bound to `?G`.
     pub fn nquads_query(prefixes: &Arc<Namespaces>) -> Result<Statement, ekg_error::Error> {
         let default_graph = DEFAULT_GRAPH_RDFOX.deref().as_display_iri();
         let statement = Statement::new(
@@ -65,7 +257,7 @@ impl Statement {
                     {{
                         GRAPH ?G {{ ?S ?P ?O }}
                     }} UNION {{
-                        ?S ?P ?P .
+                        ?S ?P ?O .
                         BIND({default_graph} AS ?G)
                     }}
                 }}
@@ -77,6 +269,213 @@ impl Statement {
     }
 }
 
+/// Builder for the common shape of a SELECT query: a projection, a basic
+/// WHERE clause and the solution modifiers, see [`Statement::select`].
+///
+/// The triple patterns and ordering expressions are taken as SPARQL text, so
+/// this is no query DSL, it only takes care of putting the pieces together.
+#[derive(Debug, Clone)]
+pub struct SelectBuilder {
+    prefixes:  Arc<Namespaces>,
+    distinct:  bool,
+    variables: Vec<String>,
+    dataset:   Vec<String>,
+    patterns:  Vec<String>,
+    order_by:  Vec<String>,
+    limit:     Option<u64>,
+    offset:    Option<u64>,
+}
+
+impl SelectBuilder {
+    pub fn distinct(mut self) -> Self {
+        self.distinct = true;
+        self
+    }
+
+    /// Add a `FROM` clause, see [`Statement::from_graph`].
+    pub fn from_graph(mut self, graph: &Graph) -> Self {
+        self.dataset
+            .push(format!("FROM {}", graph.as_display_iri()));
+        self
+    }
+
+    /// Add a `FROM NAMED` clause, see [`Statement::from_named`].
+    pub fn from_named(mut self, graph: &Graph) -> Self {
+        self.dataset
+            .push(format!("FROM NAMED {}", graph.as_display_iri()));
+        self
+    }
+
+    /// Add a graph pattern, such as `?s a ?type` or `OPTIONAL { ?s rdfs:label
+    /// ?label }`, to the WHERE clause. Patterns are joined with ` .`.
+    pub fn where_clause(mut self, pattern: &str) -> Self {
+        let pattern = pattern.trim().trim_end_matches('.').trim_end();
+        if !pattern.is_empty() {
+            self.patterns.push(pattern.to_string());
+        }
+        self
+    }
+
+    /// Add an ordering condition, such as `?name` or `DESC(?age)`.
+    pub fn order_by(mut self, condition: &str) -> Self {
+        self.order_by.push(condition.trim().to_string());
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Return the SPARQL text of the query, without the prologue.
+    pub fn to_sparql(&self) -> String {
+        let mut sparql = String::from("SELECT ");
+        if self.distinct {
+            sparql.push_str("DISTINCT ");
+        }
+        if self.variables.is_empty() {
+            sparql.push('*');
+        } else {
+            sparql.push_str(self.variables.join(" ").as_str());
+        }
+        for clause in self.dataset.iter() {
+            sparql.push_str(format!("\n{clause}").as_str());
+        }
+        sparql.push_str("\nWHERE {\n");
+        for pattern in self.patterns.iter() {
+            sparql.push_str(format!("    {pattern} .\n").as_str());
+        }
+        sparql.push('}');
+        if !self.order_by.is_empty() {
+            sparql.push_str(format!("\nORDER BY {}", self.order_by.join(" ")).as_str());
+        }
+        if let Some(limit) = self.limit {
+            sparql.push_str(format!("\nLIMIT {limit}").as_str());
+        }
+        if let Some(offset) = self.offset {
+            sparql.push_str(format!("\nOFFSET {offset}").as_str());
+        }
+        sparql
+    }
+
+    /// Build the statement, with the prefixes that the builder was created
+    /// with.
+    pub fn build(self) -> Result<Statement, ekg_error::Error> {
+        if self.patterns.is_empty() {
+            return Err(ekg_error::Error::Exception {
+                action:  "building a SELECT statement".to_string(),
+                message: "the WHERE clause has no patterns".to_string(),
+            });
+        }
+        let mut statement = Statement::new(&self.prefixes, self.to_sparql().into())?;
+        // These can be followed by more with `Statement::from_graph`
+        statement.dataset_clauses = self.dataset.len();
+        Ok(statement)
+    }
+}
+
+/// Return the byte positions of the given keyword (matched case-insensitively
+/// as a whole word) in the given SPARQL text, skipping IRIs, strings and
+/// comments.
+fn keyword_positions(text: &str, keyword: &str) -> Vec<usize> {
+    keyword_positions_at_depth(text, keyword, None)
+}
+
+/// Return the position of the given keyword outside of any `{ ... }` block.
+fn find_top_level_keyword(text: &str, keyword: &str) -> Option<usize> {
+    keyword_positions_at_depth(text, keyword, Some(0))
+        .first()
+        .copied()
+}
+
+fn keyword_positions_at_depth(text: &str, keyword: &str, at_depth: Option<usize>) -> Vec<usize> {
+    let bytes = text.as_bytes();
+    let mut positions = Vec::new();
+    let mut depth = 0_usize;
+    let mut index = 0_usize;
+    while index < bytes.len() {
+        let skip_until = |from: usize, end: u8| {
+            bytes[from..]
+                .iter()
+                .position(|byte| *byte == end)
+                .map(|pos| from + pos + 1)
+                .unwrap_or(bytes.len())
+        };
+        match bytes[index] {
+            b'<' if bytes.get(index + 1).is_some_and(|next| !next.is_ascii_whitespace()) => {
+                index = skip_until(index + 1, b'>');
+                continue;
+            }
+            quote @ (b'"' | b'\'') => {
+                index = skip_until(index + 1, quote);
+                continue;
+            }
+            b'#' => {
+                index = skip_until(index + 1, b'\n');
+                continue;
+            }
+            b'{' => depth += 1,
+            b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        let is_word_start = index == 0 || !is_word_byte(bytes[index - 1]);
+        let end = index + keyword.len();
+        if is_word_start &&
+            end <= bytes.len() &&
+            bytes[index..end].eq_ignore_ascii_case(keyword.as_bytes()) &&
+            bytes.get(end).map_or(true, |byte| !is_word_byte(*byte)) &&
+            at_depth.map_or(true, |at_depth| at_depth == depth)
+        {
+            positions.push(index);
+            index = end;
+            continue;
+        }
+        index += 1;
+    }
+    positions
+}
+
+fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b':' | b'?' | b'$')
+}
+
+fn query_type_of(text: &str) -> Result<QueryType, ekg_error::Error> {
+    let text = no_comments(text);
+    let mut rest = text.as_str().trim_start();
+    loop {
+        let keyword: String = rest
+            .chars()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .collect::<String>()
+            .to_ascii_uppercase();
+        match keyword.as_str() {
+            "PREFIX" | "BASE" => {
+                // Skip the declaration, which ends with the closing `>` of its IRI
+                match rest.find('>') {
+                    Some(pos) => rest = rest[pos + 1..].trim_start(),
+                    None => break,
+                }
+            }
+            "ASK" => return Ok(QueryType::Ask),
+            "SELECT" => return Ok(QueryType::Select),
+            "CONSTRUCT" => return Ok(QueryType::Construct),
+            "DESCRIBE" => return Ok(QueryType::Describe),
+            "INSERT" | "DELETE" | "WITH" | "LOAD" | "CLEAR" | "DROP" | "CREATE" | "ADD" |
+            "MOVE" | "COPY" => return Ok(QueryType::Update),
+            _ => break,
+        }
+    }
+    Err(ekg_error::Error::Exception {
+        action:  "determining the type of a SPARQL statement".to_string(),
+        message: format!("no query form or update operation found in:\n{text}"),
+    })
+}
+
 pub fn no_comments(string: &str) -> String {
     use std::fmt::Write;
 
@@ -154,4 +553,118 @@ mod tests {
         let actual = crate::statement::no_comments(sparql.as_str());
         assert_eq!(actual.as_str(), expected.as_str());
     }
+
+    #[test_log::test]
+    fn test_query_type() {
+        use crate::statement::{query_type_of, QueryType};
+
+        let sparql = indoc::indoc! {r##"
+            PREFIX abc: <https://whatever.org#> # ASK
+            base <https://whatever.org/>
+            # SELECT
+            ask { ?s ?p ?o }
+            "##
+        };
+        assert_eq!(query_type_of(sparql).unwrap(), QueryType::Ask);
+        assert_eq!(
+            query_type_of("SELECT * WHERE { ?s ?p ?o }").unwrap(),
+            QueryType::Select
+        );
+        assert_eq!(
+            query_type_of("PREFIX a: <a:>\nDELETE WHERE { ?s ?p ?o }").unwrap(),
+            QueryType::Update
+        );
+        assert!(query_type_of("PREFIX a: <a:>").is_err());
+    }
+
+    #[test_log::test]
+    fn test_select_builder() {
+        let prefixes = crate::Namespaces::default_namespaces().unwrap();
+        let statement = crate::Statement::select(&prefixes, &["?s", "label"])
+            .distinct()
+            .where_clause("?s a owl:Class .")
+            .where_clause("?s rdfs:label ?label")
+            .order_by("DESC(?label)")
+            .limit(10)
+            .build()
+            .unwrap();
+        assert!(statement.as_str().ends_with(indoc::indoc! {r##"
+            SELECT DISTINCT ?s ?label
+            WHERE {
+                ?s a owl:Class .
+                ?s rdfs:label ?label .
+            }
+            ORDER BY DESC(?label)
+            LIMIT 10"##
+        }));
+        assert_eq!(
+            statement.query_type().unwrap(),
+            crate::QueryType::Select
+        );
+        assert!(crate::Statement::select(&prefixes, &[])
+            .build()
+            .is_err());
+    }
+
+    #[test_log::test]
+    fn test_from_graph() {
+        let prefixes = crate::Namespaces::empty().unwrap();
+        let graph = crate::parse_graph("https://example.com/graph/a").unwrap();
+        let mut statement = crate::Statement::new(
+            &prefixes,
+            "SELECT ?s # where?\nWHERE { ?s <https://example.com/where> ?o }".into(),
+        )
+        .unwrap();
+        statement
+            .from_graph(&graph)
+            .unwrap()
+            .from_named(&graph)
+            .unwrap();
+        assert!(statement.as_str().ends_with(
+            "SELECT ?s # where?\nFROM <https://example.com/graph/a>\nFROM NAMED \
+             <https://example.com/graph/a>\nWHERE { ?s <https://example.com/where> ?o }"
+        ));
+        let mut explicit = crate::Statement::new(
+            &prefixes,
+            "ASK FROM <https://example.com/graph/b> { ?s ?p ?o }".into(),
+        )
+        .unwrap();
+        assert!(explicit.from_graph(&graph).is_err());
+    }
+
+    #[test_log::test]
+    fn test_page() {
+        let prefixes = crate::Namespaces::empty().unwrap();
+        let statement = crate::Statement::new(
+            &prefixes,
+            "SELECT ?s WHERE { { SELECT ?s WHERE { ?s ?p ?o } LIMIT 5 } } ORDER BY ?s".into(),
+        )
+        .unwrap();
+        assert!(statement.has_order_by());
+        let page = statement.page(20, 11).unwrap();
+        assert!(page.as_str().ends_with("ORDER BY ?s\nOFFSET 20\nLIMIT 11"));
+        assert!(page.page(0, 10).is_err());
+        let ask = crate::Statement::new(&prefixes, "ASK { ?s ?p ?o }".into()).unwrap();
+        assert!(!ask.has_order_by());
+        assert!(ask.page(0, 10).is_err());
+    }
+
+    #[test_log::test]
+    fn test_bind() {
+        let prefixes = crate::Namespaces::empty().unwrap();
+        let statement = crate::Statement::new(
+            &prefixes,
+            "SELECT ?name WHERE { ?$id ?p ?$idx ; <https://example.com/name> ?name }".into(),
+        )
+        .unwrap()
+        .bind("id", &crate::Term::iri("https://example.com/x").unwrap())
+        .unwrap();
+        assert!(statement.as_str().ends_with(
+            "SELECT ?name WHERE { <https://example.com/x> ?p ?$idx ; <https://example.com/name> \
+             ?name }"
+        ));
+        assert!(statement
+            .bind("unknown", &crate::Term::literal("x"))
+            .is_err());
+    }
 }
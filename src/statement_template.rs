@@ -0,0 +1,88 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    crate::{Cursor, DataStoreConnection, Namespaces, Parameters, QueryType, Statement, Term},
+    std::{borrow::Cow, sync::Arc},
+};
+
+/// A statement with parameters (written as `?$name`, see
+/// [`Statement::bind`]) whose syntax has been checked once, when it was
+/// created with [`DataStoreConnection::statement_template`], and that can
+/// then be executed with different values for its parameters.
+///
+/// This is not a prepared statement: the RDFox C API has no parameterized
+/// cursors, so nothing is compiled or cached and every execution fills in
+/// the values and compiles the resulting statement from scratch.
+#[derive(Debug)]
+pub struct StatementTemplate {
+    connection:      Arc<DataStoreConnection>,
+    statement:       Statement,
+    parameter_names: Vec<String>,
+}
+
+impl StatementTemplate {
+    pub(crate) fn new(
+        connection: &Arc<DataStoreConnection>,
+        prefixes: &Arc<Namespaces>,
+        sparql: &str,
+    ) -> Result<Self, ekg_error::Error> {
+        let statement = Statement::new(prefixes, Cow::Borrowed(sparql))?;
+        let parameter_names = parameter_names_of(statement.as_str());
+        if statement.query_type()? != QueryType::Update {
+            // Check the syntax with the parameters turned into plain variables
+            let mut as_variables = statement.clone();
+            as_variables.text = as_variables.text.replace("?$", "?");
+            as_variables.validate(connection)?;
+        }
+        Ok(Self { connection: connection.clone(), statement, parameter_names })
+    }
+
+    /// The names of the parameters (without the `?$`), in order of their
+    /// first occurrence.
+    pub fn parameter_names(&self) -> &[String] { self.parameter_names.as_slice() }
+
+    /// Return the statement with the given values filled in for its
+    /// parameters, which all have to be given.
+    pub fn bind(&self, bindings: &[(&str, &Term)]) -> Result<Statement, ekg_error::Error> {
+        if let Some(missing) = self
+            .parameter_names
+            .iter()
+            .find(|name| !bindings.iter().any(|(bound, _)| bound == name))
+        {
+            return Err(ekg_error::Error::Exception {
+                action:  "executing a statement template".to_string(),
+                message: format!("no value given for parameter ?${missing}"),
+            });
+        }
+        bindings
+            .iter()
+            .try_fold(self.statement.clone(), |statement, (name, value)| {
+                statement.bind(name, value)
+            })
+    }
+
+    /// Create a cursor for the statement with the given values filled in.
+    pub fn execute(
+        &self,
+        bindings: &[(&str, &Term)],
+        parameters: &Parameters,
+    ) -> Result<Cursor, ekg_error::Error> {
+        self.bind(bindings)?
+            .cursor(&self.connection, parameters)
+    }
+}
+
+fn parameter_names_of(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (pos, _) in text.match_indices("?$") {
+        let name: String = text[pos + 2..]
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
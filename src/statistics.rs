@@ -0,0 +1,101 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use std::fmt::{Display, Formatter};
+
+/// The size of a datastore, see
+/// [`DataStoreConnection::statistics`](crate::DataStoreConnection::statistics).
+///
+/// Every metric is optional: it's `None` when it could not be determined.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Statistics {
+    /// The number of triples in all graphs, asserted and inferred
+    pub triples:                  Option<u64>,
+    /// The number of distinct subjects and objects in all graphs
+    pub resources:                Option<u64>,
+    /// The number of distinct predicates in all graphs
+    pub predicates:               Option<u64>,
+    /// The number of Datalog rules
+    pub rules:                    Option<u64>,
+    /// The number of bytes in use by the whole server, that is its memory
+    /// limit minus what's still available, RDFox does not report the memory
+    /// use of a single datastore
+    pub server_memory_used_bytes: Option<u64>,
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let metric = |value: Option<u64>| {
+            value
+                .map(|value| value.to_string())
+                .unwrap_or_else(|| "?".to_string())
+        };
+        write!(
+            f,
+            "{} triples, {} resources, {} predicates, {} rules, {} bytes used by the server",
+            metric(self.triples),
+            metric(self.resources),
+            metric(self.predicates),
+            metric(self.rules),
+            metric(self.server_memory_used_bytes)
+        )
+    }
+}
+
+/// Count the rules in the given Datalog, as exported by RDFox, by counting
+/// the `:-` between the head and the body of every rule, skipping comments,
+/// IRIs and string literals in which `:-` can occur as well.
+pub(crate) fn count_rules(datalog: &str) -> u64 {
+    let mut count = 0_u64;
+    let mut chars = datalog.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' | '#' => {
+                chars.by_ref().take_while(|c| *c != '\n').for_each(drop);
+            }
+            '"' | '\'' => {
+                while let Some(next) = chars.next() {
+                    match next {
+                        '\\' => {
+                            chars.next();
+                        }
+                        next if next == c => break,
+                        _ => {}
+                    }
+                }
+            }
+            '<' => {
+                // An IRI has no whitespace, unlike a `<` comparison
+                let length = chars
+                    .clone()
+                    .take_while(|c| *c != '>' && !c.is_whitespace())
+                    .count();
+                if chars.clone().nth(length) == Some('>') {
+                    chars.by_ref().take(length + 1).for_each(drop);
+                }
+            }
+            ':' if chars.peek() == Some(&'-') => {
+                chars.next();
+                count += 1;
+            }
+            _ => {}
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    #[test_log::test]
+    fn test_count_rules() {
+        let datalog = r#"
+            @prefix ex: <https://example.com/a:-b#> .
+            % a comment mentioning :- in passing
+            [?x, a, ex:Adult] :- [?x, ex:age, ?age], FILTER(?age >= 18) .
+            [?x, ex:note, "a :- b"] :- [?x, a, <https://example.com/x:-y>] .
+            [?x, ex:small, true] :- [?x, ex:size, ?size], FILTER(?size < 10) .
+        "#;
+        assert_eq!(super::count_rules(datalog), 3);
+        assert_eq!(super::count_rules(""), 0);
+    }
+}
@@ -3,8 +3,11 @@
 
 use {
     crate::{
+        callback_context::CallbackContext,
+        cancellation::run_with_timeout,
         database_call,
         DataStoreConnection,
+        FactDomain,
         Parameters,
         rdfox_api::{
             CDataStoreConnection,
@@ -14,32 +17,17 @@ use {
         },
         Statement,
     },
-    ekg_namespace::{Namespace, ptr_to_cstr},
+    ekg_namespace::Namespace,
     mime::Mime,
     std::{
         ffi::{c_void, CString},
         fmt::Debug,
         io::Write,
         mem::MaybeUninit,
-        ptr,
         sync::Arc,
     },
 };
 
-#[derive(PartialEq, Debug)]
-struct RefToSelf<'a, W: 'a + Write> {
-    streamer: *mut Streamer<'a, W>,
-}
-
-impl<'a, W: 'a + Write> Drop for RefToSelf<'a, W> {
-    fn drop(&mut self) {
-        tracing::trace!(
-            "{:p}: Dropping RefToSelf ({self:p})",
-            self.streamer
-        );
-    }
-}
-
 /// A `Streamer` is a helper-object that's created by `evaluate_to_stream`
 /// to handle the various callbacks from the underlying C-API to RDFox.
 #[derive(Debug)]
@@ -47,11 +35,18 @@ pub struct Streamer<'a, W: 'a + Write> {
     pub connection: Arc<DataStoreConnection>,
     pub writer: W,
     pub statement: &'a Statement,
-    pub mime_type: &'static Mime,
-    pub base_iri: Namespace,
+    pub mime_type: Mime,
+    /// The base IRI, if one is meaningful for the output format. Note that
+    /// the C API takes no base IRI when evaluating a statement, relative IRIs
+    /// in the statement itself are resolved against its `BASE` declaration.
+    pub base_iri: Option<Namespace>,
+    pub fact_domain: FactDomain,
     pub instant: std::time::Instant,
+    number_of_solutions: u64,
+    /// The parameters to evaluate the statement with, on top of the default
+    /// ones that only select the fact domain.
+    parameters: Option<&'a Parameters>,
     self_p: String,
-    remaining_buffer: std::cell::RefCell<Option<String>>,
 }
 
 impl<'a, W: 'a + Write> Drop for Streamer<'a, W> {
@@ -61,12 +56,80 @@ impl<'a, W: 'a + Write> Drop for Streamer<'a, W> {
 }
 
 impl<'a, W: 'a + Write> Streamer<'a, W> {
+    /// Evaluate the given statement and stream the results, serialized in
+    /// the given format, to the given writer.
+    ///
+    /// The `mime_type` is owned so that a format that has been chosen at
+    /// runtime (for instance negotiated from an HTTP `Accept` header) can be
+    /// passed in just as easily as one of the predefined constants.
     pub fn run(
         connection: &Arc<DataStoreConnection>,
         writer: W,
         statement: &'a Statement,
-        mime_type: &'static Mime,
-        base_iri: Namespace,
+        mime_type: Mime,
+        base_iri: Option<Namespace>,
+    ) -> Result<Self, ekg_error::Error> {
+        Self::run_in_fact_domain(
+            connection,
+            writer,
+            statement,
+            mime_type,
+            base_iri,
+            FactDomain::ALL,
+        )
+    }
+
+    /// Same as [`run`](Self::run), which matches all facts, but only
+    /// matching the facts in the given fact domain.
+    pub fn run_in_fact_domain(
+        connection: &Arc<DataStoreConnection>,
+        writer: W,
+        statement: &'a Statement,
+        mime_type: Mime,
+        base_iri: Option<Namespace>,
+        fact_domain: FactDomain,
+    ) -> Result<Self, ekg_error::Error> {
+        Self::start(
+            connection,
+            writer,
+            statement,
+            mime_type,
+            base_iri,
+            fact_domain,
+            None,
+        )
+    }
+
+    /// Same as [`run`](Self::run) but evaluating the statement with the
+    /// given parameters, if any, on top of the default ones. A fact domain
+    /// in the given parameters overrides the default, [`FactDomain::ALL`].
+    pub fn run_with_parameters(
+        connection: &Arc<DataStoreConnection>,
+        writer: W,
+        statement: &'a Statement,
+        mime_type: Mime,
+        base_iri: Option<Namespace>,
+        parameters: Option<&'a Parameters>,
+    ) -> Result<Self, ekg_error::Error> {
+        Self::start(
+            connection,
+            writer,
+            statement,
+            mime_type,
+            base_iri,
+            FactDomain::ALL,
+            parameters,
+        )
+    }
+
+    fn start(
+        connection: &Arc<DataStoreConnection>,
+        writer: W,
+        statement: &'a Statement,
+        mime_type: Mime,
+        base_iri: Option<Namespace>,
+        fact_domain: FactDomain,
+        parameters: Option<&'a Parameters>,
     ) -> Result<Self, ekg_error::Error> {
         let streamer = Self {
             connection: connection.clone(),
@@ -74,73 +137,95 @@ impl<'a, W: 'a + Write> Streamer<'a, W> {
             statement,
             mime_type,
             base_iri,
+            fact_domain,
             instant: std::time::Instant::now(),
+            number_of_solutions: 0,
+            parameters,
             self_p: "".to_string(),
-            remaining_buffer: std::cell::RefCell::default(),
         };
         streamer.evaluate()
     }
 
     /// Evaluate/execute the statement and stream all content to the given
     /// writer, then return the streamer (i.e. self).
-    fn evaluate(mut self) -> Result<Self, ekg_error::Error> {
-        let statement_text = self.statement.as_c_string()?;
+    fn evaluate(self) -> Result<Self, ekg_error::Error> {
+        let mut streamer = CallbackContext::new(self);
+        let statement_text = streamer.statement.as_c_string()?;
         let statement_text_len = statement_text.as_bytes().len();
-        let parameters = Parameters::empty()?.fact_domain(crate::FactDomain::ALL)?;
-        let query_answer_format_name = CString::new(self.mime_type.as_ref())?;
+        let mut parameters = Parameters::empty()?.fact_domain(streamer.fact_domain)?;
+        if let Some(overrides) = streamer.parameters {
+            parameters = parameters.merge(overrides)?;
+        }
+        let query_answer_format_name = CString::new(streamer.mime_type.as_ref())?;
         let mut statement_result = MaybeUninit::<CStatementResult>::uninit();
-        let connection_ptr = self.connection_ptr();
+        let connection = streamer.connection.clone();
+        let connection_ptr = streamer.connection_ptr();
+        let timeout = streamer.statement.timeout;
 
-        let self_p = format!("{:p}", &self);
-        self.self_p = self_p.clone();
+        let self_p = format!("{:p}", streamer.as_ref());
+        streamer.self_p = self_p.clone();
 
-        tracing::debug!("{self_p}: evaluate statement with mime={query_answer_format_name:?}");
+        let span = tracing::debug_span!(
+            target: ekg_namespace::consts::LOG_TARGET_DATABASE,
+            "evaluate",
+            conn = connection.number,
+            mime = %streamer.mime_type,
+            fact_domain = ?streamer.fact_domain,
+            solutions = tracing::field::Empty,
+            elapsed_ms = tracing::field::Empty,
+        );
+        let _entered = span.enter();
 
-        let ref_to_self = Box::new(RefToSelf { streamer: &mut self as *mut Self });
-        let ref_to_self_raw_ptr = Box::into_raw(ref_to_self);
+        tracing::debug!("{self_p}: evaluate statement with mime={query_answer_format_name:?}");
 
-        let stream = Box::new(COutputStream {
-            context: ref_to_self_raw_ptr as *mut _,
+        let stream = COutputStream {
+            context: streamer.as_context(),
             flushFn: Some(Self::flush_function),
             writeFn: Some(Self::write_function),
-        });
-        let stream_raw_ptr = Box::into_raw(stream);
-
-        let result = database_call! {
-            "evaluating a statement",
-            CDataStoreConnection_evaluateStatement(
-                connection_ptr,
-                statement_text.as_ptr(),
-                statement_text_len,
-                parameters.inner.cast_const(),
-                stream_raw_ptr as *const COutputStream,
-                query_answer_format_name.as_ptr(),
-                statement_result.as_mut_ptr(),
-            )
         };
-        // std::thread::sleep(std::time::Duration::from_millis(1000));
-        // Explicitly clean up the two boxes that we allocated
-        unsafe {
-            ptr::drop_in_place(ref_to_self_raw_ptr);
-            ptr::drop_in_place(stream_raw_ptr);
+
+        let result = run_with_timeout(&connection, timeout, || {
+            database_call! {
+                "evaluating a statement",
+                CDataStoreConnection_evaluateStatement(
+                    connection_ptr,
+                    statement_text.as_ptr(),
+                    statement_text_len,
+                    parameters.inner.cast_const(),
+                    &stream,
+                    query_answer_format_name.as_ptr(),
+                    statement_result.as_mut_ptr(),
+                )
+            }
+        });
+
+        // An error from the writer takes precedence over whatever RDFox reports
+        // after we told it to abort the stream
+        if let Some(err) = streamer.take_error() {
+            tracing::error!("{self_p}: could not write: {err:?}");
+            return Err(err.into());
         }
 
-        let statement_result = unsafe { statement_result.assume_init() };
+        result?;
 
-        result?; // we're doing this after the drop_in_place calls to avoid memory leak
+        let statement_result = unsafe { statement_result.assume_init() };
 
         tracing::debug!("{self_p}: statement_result={statement_result:?}");
-        Ok(self)
+        streamer.number_of_solutions = statement_result.numberOfQueryAnswers as u64;
+        span.record("solutions", streamer.number_of_solutions);
+        span.record(
+            "elapsed_ms",
+            streamer.instant.elapsed().as_millis() as u64,
+        );
+        Ok(streamer.into_inner())
     }
 
-    unsafe fn context_as_ref_to_self(context: *mut c_void) -> &'a mut RefToSelf<'a, W> {
-        let ref_to_self = context as *mut RefToSelf<'a, W>;
-        &mut *ref_to_self
-    }
+    /// The number of solutions (i.e. answers or triples) that RDFox reported
+    /// after evaluating the statement, zero if nothing matched.
+    pub fn number_of_solutions(&self) -> u64 { self.number_of_solutions }
 
     extern "C" fn flush_function(context: *mut c_void) -> bool {
-        let ref_to_self = unsafe { Self::context_as_ref_to_self(context) };
-        let streamer = unsafe { &mut *ref_to_self.streamer };
+        let streamer = unsafe { CallbackContext::<Self>::from_context(context) };
         tracing::trace!("{streamer:p}: flush_function");
         streamer.flush()
     }
@@ -150,61 +235,24 @@ impl<'a, W: 'a + Write> Streamer<'a, W> {
         data: *const c_void,
         number_of_bytes_to_write: usize,
     ) -> bool {
-        let ref_to_self = unsafe { Self::context_as_ref_to_self(context) };
-        let streamer = unsafe { &mut *ref_to_self.streamer };
+        let streamer = unsafe { CallbackContext::<Self>::from_context(context) };
 
         tracing::trace!("{streamer:p}: write_function");
 
-        let result = match ptr_to_cstr(data as *const u8, number_of_bytes_to_write) {
-            Ok(data_c_str) => {
-                tracing::trace!("{streamer:p}: writing {number_of_bytes_to_write} bytes (a)");
-                let data = if streamer.remaining_buffer.borrow().is_some() {
-                    // If we have some remaining bytes from the previous call to `write_function`
-                    // then concatenate them here with the new buffer..
-                    [
-                        streamer
-                            .remaining_buffer
-                            .borrow()
-                            .as_ref()
-                            .unwrap()
-                            .as_bytes(),
-                        data_c_str.to_bytes_with_nul(),
-                    ]
-                        .concat()
-                } else {
-                    data_c_str.to_bytes_with_nul().to_vec()
-                };
-                let data_len = data.len();
-                match streamer.writer.write(&data) {
-                    Ok(len) => {
-                        tracing::trace!(
-                            "{streamer:p}: wrote {len} bytes out of {}",
-                            data_len
-                        );
-                        if len < data_len {
-                            // When we didn't process the last part of the buffer (probably because
-                            // the last N-Triple line was not complete), then save the remainder
-                            // in `remaining_buffer` for the next call to `write_function`
-                            streamer.remaining_buffer.replace(Some(unsafe {
-                                String::from_utf8_unchecked(data[len..].to_vec())
-                            }));
-                            tracing::trace!(
-                                "{streamer:p}: remaining buffer: {}",
-                                streamer.remaining_buffer.borrow().as_ref().unwrap()
-                            );
-                        } else {
-                            streamer.remaining_buffer.replace(None);
-                        }
-                        true
-                    }
-                    Err(err) => {
-                        panic!("{streamer:p}: could not write: {err:?}")
-                    }
-                }
-            }
-            Err(error) => {
-                tracing::error!("{streamer:p}: could not write: {error:?}");
-                false
+        // The buffer is not a C string, RDFox tells us exactly how many bytes
+        // to write so we pass them on as is, without looking for (or adding) a
+        // terminating NUL.
+        let data = if number_of_bytes_to_write == 0 {
+            &[][..]
+        } else {
+            unsafe { std::slice::from_raw_parts(data as *const u8, number_of_bytes_to_write) }
+        };
+        tracing::trace!("{streamer:p}: writing {number_of_bytes_to_write} bytes");
+        let result = match streamer.writer.write_all(data) {
+            Ok(()) => true,
+            Err(err) => {
+                tracing::error!("{streamer:p}: could not write: {err:?}");
+                streamer.fail(err)
             }
         };
         tracing::trace!("{streamer:p}: write_function result={result}");
@@ -219,11 +267,12 @@ trait StreamerWithCallbacks {
     // fn write(&mut self, data: &[u8]) -> bool;
 }
 
-impl<'a, W: 'a + Write> StreamerWithCallbacks for Streamer<'a, W> {
+impl<'a, W: 'a + Write> StreamerWithCallbacks for CallbackContext<Streamer<'a, W>> {
     fn flush(&mut self) -> bool {
         tracing::trace!("{self:p}: flush");
         let y = if let Err(err) = self.writer.flush() {
-            panic!("{self:p}: Could not flush: {err:?}")
+            tracing::error!("{self:p}: could not flush: {err:?}");
+            self.fail(err)
         } else {
             true
         };
@@ -0,0 +1,51 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    crate::DataStoreConnection,
+    std::{
+        ops::Deref,
+        sync::{Arc, Mutex, MutexGuard},
+    },
+};
+
+/// A [`DataStoreConnection`] that can safely be shared between threads: every
+/// thread has to [`lock`](SyncConnection::lock) it first, so that the calls
+/// into RDFox on the underlying connection are serialized.
+///
+/// Note that a transaction spans multiple calls, so keep the guard for as
+/// long as the transaction is active. To run things in parallel, use a
+/// [`ConnectionPool`](crate::ConnectionPool) instead.
+#[derive(Debug)]
+pub struct SyncConnection {
+    connection: Arc<DataStoreConnection>,
+    lock:       Mutex<()>,
+}
+
+/// Gives exclusive use of the connection of a [`SyncConnection`] until it is
+/// dropped.
+pub struct SyncConnectionGuard<'a> {
+    connection: &'a Arc<DataStoreConnection>,
+    _guard:     MutexGuard<'a, ()>,
+}
+
+impl<'a> Deref for SyncConnectionGuard<'a> {
+    type Target = Arc<DataStoreConnection>;
+
+    fn deref(&self) -> &Self::Target { self.connection }
+}
+
+impl SyncConnection {
+    pub fn new(connection: Arc<DataStoreConnection>) -> Self {
+        Self { connection, lock: Mutex::new(()) }
+    }
+
+    /// Wait until no other thread uses the connection and return a guard
+    /// that gives access to it.
+    pub fn lock(&self) -> SyncConnectionGuard<'_> {
+        // A panic in another thread that held the lock does not make the
+        // connection itself unusable (its transaction was rolled back)
+        let guard = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        SyncConnectionGuard { connection: &self.connection, _guard: guard }
+    }
+}
@@ -0,0 +1,356 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    crate::{Namespaces, RDF_TYPE},
+    ekg_namespace::DataType,
+    std::fmt::{Display, Formatter},
+};
+
+/// An RDF term (an IRI, a blank node or a literal) that knows how to render
+/// itself in SPARQL and N-Triples syntax, with all the escaping that requires.
+///
+/// The constructors perform basic sanity checks (no full RFC 3987 parsing)
+/// so that malformed terms are caught before they reach RDFox.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Term {
+    Iri(String),
+    BlankNode(String),
+    Literal {
+        lexical_form: String,
+        datatype:     Option<String>,
+        language:     Option<String>,
+    },
+}
+
+impl Display for Term {
+    /// Show the term in SPARQL syntax
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Term::Iri(iri) => write!(f, "<{iri}>"),
+            Term::BlankNode(label) => write!(f, "_:{label}"),
+            Term::Literal { lexical_form, datatype, language } => {
+                write!(f, "\"{}\"", escape_string(lexical_form))?;
+                if let Some(language) = language {
+                    write!(f, "@{language}")
+                } else if let Some(datatype) = datatype {
+                    write!(f, "^^<{datatype}>")
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+impl Term {
+    /// An IRI, which is checked for the characters that are not allowed in an
+    /// IRI reference in SPARQL (so that they cannot be used to break out of
+    /// the `<...>` brackets).
+    pub fn iri(iri: &str) -> Result<Self, ekg_error::Error> {
+        if iri.is_empty() {
+            return Err(invalid_term("IRI is empty".to_string()));
+        }
+        if let Some(c) = iri.chars().find(|c| is_invalid_iri_char(*c)) {
+            return Err(invalid_term(format!(
+                "IRI <{iri}> contains invalid character {c:?}"
+            )));
+        }
+        Ok(Term::Iri(iri.to_string()))
+    }
+
+    /// The IRI of a compact IRI such as `ex:Person`, expanded with the given
+    /// namespaces (see [`Namespaces::expand_curie`]), where `a` stands for
+    /// `rdf:type` like it does in SPARQL and Turtle.
+    pub fn curie(curie: &str, namespaces: &Namespaces) -> Result<Self, ekg_error::Error> {
+        if curie == "a" {
+            return Ok(Self::rdf_type());
+        }
+        Self::iri(namespaces.expand_curie(curie)?.as_str())
+    }
+
+    /// The `rdf:type` predicate, see [`RDF_TYPE`]
+    pub fn rdf_type() -> Self { Term::Iri(RDF_TYPE.to_string()) }
+
+    /// A blank node with the given label (without the `_:`)
+    pub fn blank_node(label: &str) -> Result<Self, ekg_error::Error> {
+        let valid = label
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_') &&
+            label
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.')) &&
+            !label.ends_with('.');
+        if !valid {
+            return Err(invalid_term(format!(
+                "invalid blank node label {label:?}"
+            )));
+        }
+        Ok(Term::BlankNode(label.to_string()))
+    }
+
+    /// A plain string literal
+    pub fn literal(value: &str) -> Self {
+        Term::Literal {
+            lexical_form: value.to_string(),
+            datatype:     None,
+            language:     None,
+        }
+    }
+
+    /// A literal with the given datatype IRI, such as
+    /// `http://www.w3.org/2001/XMLSchema#integer`
+    pub fn typed_literal(value: &str, datatype_iri: &str) -> Result<Self, ekg_error::Error> {
+        let Term::Iri(datatype) = Self::iri(datatype_iri)? else {
+            unreachable!()
+        };
+        Ok(Term::Literal {
+            lexical_form: value.to_string(),
+            datatype:     Some(datatype),
+            language:     None,
+        })
+    }
+
+    /// A literal with a language tag such as `en` or `en-GB`
+    pub fn lang_literal(value: &str, language: &str) -> Result<Self, ekg_error::Error> {
+        if !is_valid_language_tag(language) {
+            return Err(invalid_term(format!(
+                "invalid language tag {language:?}"
+            )));
+        }
+        Ok(Term::Literal {
+            lexical_form: value.to_string(),
+            datatype:     None,
+            language:     Some(language.to_string()),
+        })
+    }
+
+    pub fn is_iri(&self) -> bool { matches!(self, Term::Iri(_)) }
+
+    pub fn is_blank_node(&self) -> bool { matches!(self, Term::BlankNode(_)) }
+
+    pub fn is_literal(&self) -> bool { matches!(self, Term::Literal { .. }) }
+
+    /// Return the language tag of a language-tagged literal.
+    pub fn language(&self) -> Option<&str> {
+        match self {
+            Term::Literal { language, .. } => language.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Return the datatype IRI of a typed literal.
+    pub fn datatype(&self) -> Option<&str> {
+        match self {
+            Term::Literal { datatype, .. } => datatype.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Return the IRI without the angle brackets, or the label of the blank
+    /// node, or the lexical form of the literal.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Term::Iri(iri) => iri.as_str(),
+            Term::BlankNode(label) => label.as_str(),
+            Term::Literal { lexical_form, .. } => lexical_form.as_str(),
+        }
+    }
+
+    /// Render the term in SPARQL syntax, the same as its `Display`
+    pub fn as_sparql(&self) -> String { self.to_string() }
+
+    /// Render the term in N-Triples syntax. For the terms that we support,
+    /// including the escaping of literals, this is the same as SPARQL syntax.
+    pub fn as_ntriples(&self) -> String { self.to_string() }
+}
+
+impl Term {
+    /// Create a term from the lexical form and datatype ID that RDFox returns
+    /// for a resource, `None` for an unbound value.
+    pub(crate) fn from_rdfox(
+        datatype_id: u8,
+        lexical_form: &str,
+    ) -> Result<Option<Self>, ekg_error::Error> {
+        let term = match DataType::from_datatype_id(datatype_id)? {
+            DataType::UnboundValue => return Ok(None),
+            DataType::BlankNode => Term::BlankNode(lexical_form.to_string()),
+            DataType::IriReference => Term::Iri(lexical_form.to_string()),
+            DataType::String => Term::literal(lexical_form),
+            DataType::PlainLiteral => {
+                // rdf:PlainLiteral, of which RDFox returns the lexical form as
+                // `text@language`
+                match lexical_form.rsplit_once('@') {
+                    Some((value, language)) if !language.is_empty() => {
+                        Term::lang_literal(value, language)?
+                    }
+                    _ => Term::literal(lexical_form.trim_end_matches('@')),
+                }
+            }
+            data_type => {
+                let Some(local_name) = xsd_local_name(data_type) else {
+                    return Err(invalid_term(format!(
+                        "no datatype IRI for RDFox datatype {data_type:?} of {lexical_form:?}"
+                    )));
+                };
+                Term::Literal {
+                    lexical_form: lexical_form.to_string(),
+                    datatype:     Some(format!("{XSD_NAMESPACE}{local_name}")),
+                    language:     None,
+                }
+            }
+        };
+        Ok(Some(term))
+    }
+}
+
+const XSD_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// The local name, in the XSD namespace, of the datatype of a typed literal.
+fn xsd_local_name(data_type: DataType) -> Option<&'static str> {
+    let local_name = match data_type {
+        DataType::Boolean => "boolean",
+        DataType::DateTime => "dateTime",
+        DataType::DateTimeStamp => "dateTimeStamp",
+        DataType::Time => "time",
+        DataType::Date => "date",
+        DataType::YearMonth => "gYearMonth",
+        DataType::Year => "gYear",
+        DataType::MonthDay => "gMonthDay",
+        DataType::Day => "gDay",
+        DataType::Month => "gMonth",
+        DataType::Duration => "duration",
+        DataType::YearMonthDuration => "yearMonthDuration",
+        DataType::DayTimeDuration => "dayTimeDuration",
+        DataType::Double => "double",
+        DataType::Float => "float",
+        DataType::Decimal => "decimal",
+        DataType::Integer => "integer",
+        DataType::NonNegativeInteger => "nonNegativeInteger",
+        DataType::PositiveInteger => "positiveInteger",
+        DataType::NonPositiveInteger => "nonPositiveInteger",
+        DataType::NegativeInteger => "negativeInteger",
+        DataType::Long => "long",
+        DataType::Int => "int",
+        DataType::Short => "short",
+        DataType::Byte => "byte",
+        DataType::UnsignedLong => "unsignedLong",
+        DataType::UnsignedInt => "unsignedInt",
+        DataType::UnsignedShort => "unsignedShort",
+        DataType::UnsignedByte => "unsignedByte",
+        _ => return None,
+    };
+    Some(local_name)
+}
+
+fn invalid_term(message: String) -> ekg_error::Error {
+    ekg_error::Error::Exception { action: "creating an RDF term".to_string(), message }
+}
+
+fn is_invalid_iri_char(c: char) -> bool {
+    c <= ' ' || matches!(c, '<' | '>' | '"' | '{' | '}' | '|' | '^' | '`' | '\\')
+}
+
+fn is_valid_language_tag(tag: &str) -> bool {
+    let mut parts = tag.split('-');
+    let primary = parts.next().unwrap_or_default();
+    !primary.is_empty() &&
+        primary.chars().all(|c| c.is_ascii_alphabetic()) &&
+        parts.all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// Escape the given string so that it can be put between double quotes in
+/// SPARQL (or Turtle or N-Triples).
+fn escape_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\u{08}' => escaped.push_str("\\b"),
+            '\u{0C}' => escaped.push_str("\\f"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Term;
+
+    #[test_log::test]
+    fn test_curie() {
+        let namespaces = crate::Namespaces::from_prologue("PREFIX ex: <https://example.com/>")
+            .unwrap();
+        assert_eq!(
+            Term::curie("ex:Person", &namespaces).unwrap(),
+            Term::iri("https://example.com/Person").unwrap()
+        );
+        assert_eq!(
+            Term::curie("a", &namespaces).unwrap(),
+            Term::rdf_type()
+        );
+        assert!(Term::curie("foo:Person", &namespaces).is_err());
+        assert!(Term::curie("Person", &namespaces).is_err());
+    }
+
+    #[test_log::test]
+    fn test_term_as_sparql() {
+        assert_eq!(
+            Term::iri("https://example.com/x").unwrap().to_string(),
+            "<https://example.com/x>"
+        );
+        assert!(Term::iri("https://example.com/x> } ; DROP ALL ; {").is_err());
+        assert_eq!(
+            Term::literal("say \"hi\"\n").to_string(),
+            r#""say \"hi\"\n""#
+        );
+        assert_eq!(
+            Term::lang_literal("colour", "en-GB").unwrap().to_string(),
+            r#""colour"@en-GB"#
+        );
+        assert!(Term::lang_literal("colour", "en GB").is_err());
+        assert!(Term::iri("").is_err());
+        assert!(Term::iri("https://example.com/\u{7}").is_err());
+        assert_eq!(
+            Term::blank_node("b0").unwrap().as_ntriples(),
+            "_:b0"
+        );
+        assert!(Term::blank_node("b0.").is_err());
+        assert_eq!(
+            Term::typed_literal("42", "http://www.w3.org/2001/XMLSchema#integer")
+                .unwrap()
+                .to_string(),
+            r#""42"^^<http://www.w3.org/2001/XMLSchema#integer>"#
+        );
+    }
+
+    #[test_log::test]
+    fn test_term_from_rdfox() {
+        assert_eq!(Term::from_rdfox(0, "").unwrap(), None);
+        assert_eq!(
+            Term::from_rdfox(2, "https://example.com/x").unwrap(),
+            Some(Term::Iri("https://example.com/x".to_string()))
+        );
+        assert_eq!(
+            Term::from_rdfox(4, "colour@en-GB").unwrap(),
+            Some(Term::lang_literal("colour", "en-GB").unwrap())
+        );
+        assert_eq!(
+            Term::from_rdfox(21, "42").unwrap().unwrap().to_string(),
+            r#""42"^^<http://www.w3.org/2001/XMLSchema#integer>"#
+        );
+        let datatype = |id: u8| {
+            Term::from_rdfox(id, "1").unwrap().unwrap().datatype().unwrap().to_string()
+        };
+        assert_eq!(datatype(23), "http://www.w3.org/2001/XMLSchema#positiveInteger");
+        assert_eq!(datatype(24), "http://www.w3.org/2001/XMLSchema#nonPositiveInteger");
+        assert_eq!(datatype(27), "http://www.w3.org/2001/XMLSchema#int");
+        assert_eq!(datatype(33), "http://www.w3.org/2001/XMLSchema#unsignedByte");
+    }
+}
@@ -5,6 +5,7 @@ use {
     crate::{
         database_call,
         DataStoreConnection,
+        is_retryable,
         rdfox_api::{
             CDataStoreConnection_beginTransaction,
             CDataStoreConnection_commitTransaction,
@@ -15,16 +16,85 @@ use {
     ,
     std::{
         fmt::{Display, Formatter},
+        ops::Deref,
         sync::{Arc, atomic::AtomicBool},
+        time::Duration,
     },
 };
 
+/// The kind of a [`Transaction`], as chosen when it was begun.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionType {
+    ReadOnly,
+    ReadWrite,
+    #[cfg(not(feature = "rdfox-7-0"))]
+    Exclusive,
+}
+
+impl From<CTransactionType> for TransactionType {
+    fn from(tx_type: CTransactionType) -> Self {
+        match tx_type {
+            #[cfg(not(feature = "rdfox-7-0"))]
+            CTransactionType::TRANSACTION_TYPE_EXCLUSIVE => TransactionType::Exclusive,
+            CTransactionType::TRANSACTION_TYPE_READ_ONLY => TransactionType::ReadOnly,
+            CTransactionType::TRANSACTION_TYPE_READ_WRITE => TransactionType::ReadWrite,
+        }
+    }
+}
+
+/// A transaction on a [`DataStoreConnection`].
+///
+/// A `Transaction` acts as a guard: when the last `Arc` to a transaction that
+/// has not been committed is dropped, the transaction is rolled back. This
+/// makes it safe to use `?` for early returns in the middle of a sequence of
+/// statements:
+///
+/// ```ignore
+/// let tx = Transaction::begin_read_write(&connection)?;
+/// connection.evaluate_update(&insert, &parameters)?; // rolled back on error
+/// connection.evaluate_update(&delete, &parameters)?;
+/// tx.commit()?;
+/// ```
+///
+/// The same holds when a panic unwinds through the scope that owns the
+/// transaction: it's rolled back during the unwind, and if that rollback
+/// fails the error is logged rather than causing a second panic (which
+/// would abort the process).
+///
+/// # Thread safety
+///
+/// A `Transaction` is `Send` and `Sync`, without any `unsafe impl`: it holds
+/// no RDFox pointer of its own, only an `Arc` to its [`DataStoreConnection`],
+/// and its commit state is an atomic. Note however that RDFox requires a
+/// connection to be used by one thread at a time, so while a transaction can
+/// be handed to another thread, statements should not be evaluated in it
+/// from several threads concurrently; use a connection per thread instead.
 #[derive(Debug)]
 pub struct Transaction {
     pub connection: Arc<DataStoreConnection>,
     committed: AtomicBool,
     tx_type: CTransactionType,
     number: usize,
+    /// The version of the datastore when the transaction began
+    version_at_begin: Option<u64>,
+}
+
+// Fail to compile if a future field makes `Transaction` lose `Send` or
+// `Sync`, it's shared via `Arc` between cursors (see "Thread safety" above).
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Transaction>();
+};
+
+/// What a commit did, see [`Transaction::commit_with_info`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitInfo {
+    /// True if the datastore changed, for instance so that a cache knows it
+    /// has to be invalidated. True as well when that could not be determined.
+    pub changed: bool,
+    /// The version of the datastore after the commit, RDFox increments it
+    /// with every change
+    pub version: Option<u64>,
 }
 
 impl Drop for Transaction {
@@ -37,7 +107,18 @@ impl Drop for Transaction {
                 "Ended {self:}"
             );
         } else if let Err(err) = self._rollback() {
-            panic!("{self:} could not be rolled back: {err}", );
+            // Panicking while already unwinding would abort the process, so in
+            // that case we only log the failed rollback
+            if std::thread::panicking() {
+                tracing::error!(
+                    target: ekg_namespace::consts::LOG_TARGET_DATABASE,
+                    txno = self.number,
+                    conn = self.connection.number,
+                    "{self:} could not be rolled back while unwinding: {err}"
+                );
+            } else {
+                panic!("{self:} could not be rolled back: {err}", );
+            }
         }
     }
 }
@@ -64,12 +145,17 @@ impl Transaction {
             connection.inner,
             tx_type
         ))?;
+        connection
+            .transaction_open
+            .store(true, std::sync::atomic::Ordering::Relaxed);
         let tx = Arc::new(Self {
             connection: connection.clone(),
             committed: AtomicBool::new(false),
             number,
             tx_type,
+            version_at_begin: connection.data_store_version().ok(),
         });
+        *connection.current_transaction.lock().unwrap() = Arc::downgrade(&tx);
         tracing::debug!(
             target: ekg_namespace::consts::LOG_TARGET_DATABASE,
             txno = tx.number,
@@ -79,6 +165,18 @@ impl Transaction {
         Ok(tx)
     }
 
+    /// Return whether this transaction was begun read-only or read/write.
+    pub fn transaction_type(&self) -> TransactionType { self.tx_type.into() }
+
+    /// Return true if this transaction was begun with
+    /// [`begin_read_only`](Self::begin_read_only), in which case any attempt
+    /// to update the datastore will fail.
+    pub fn is_read_only(&self) -> bool { self.transaction_type() == TransactionType::ReadOnly }
+
+    /// Return true as long as this transaction has not been committed or
+    /// rolled back.
+    pub fn is_active(&self) -> bool { !self.committed.load(std::sync::atomic::Ordering::Relaxed) }
+
     fn get_title(&self) -> String {
         Self::get_title_for(self.tx_type, self.number, self.connection.number)
     }
@@ -122,6 +220,42 @@ impl Transaction {
         )
     }
 
+    /// Begin a read/write transaction on the given connection, or join the
+    /// transaction that is already active on it.
+    ///
+    /// RDFox does not support savepoints, so a joined transaction is simply
+    /// the outer transaction: calling [`TransactionScope::commit`] on it is a
+    /// no-op and only the outermost scope actually commits. This allows
+    /// functions that each want to run in a transaction to be composed
+    /// without failing on a second begin.
+    ///
+    /// Only a read/write transaction is joined: if the active transaction is
+    /// read-only an error is returned, rather than handing out a scope in
+    /// which the first write would fail.
+    pub fn begin_or_join(
+        connection: &Arc<DataStoreConnection>,
+    ) -> Result<TransactionScope, ekg_error::Error> {
+        if let Some(tx) = connection.active_transaction() {
+            if tx.is_read_only() {
+                return Err(ekg_error::Error::Exception {
+                    action:  "beginning or joining a read/write transaction".to_string(),
+                    message: format!("{tx} is read-only and cannot be joined for writing"),
+                });
+            }
+            tracing::trace!(
+                target: ekg_namespace::consts::LOG_TARGET_DATABASE,
+                txno = tx.number,
+                conn = connection.number,
+                "Joining {tx:}"
+            );
+            return Ok(TransactionScope { tx, outermost: false });
+        }
+        Ok(TransactionScope {
+            tx: Self::begin_read_write(connection)?,
+            outermost: true,
+        })
+    }
+
     pub fn begin_read_write_do<T, F>(
         connection: &Arc<DataStoreConnection>,
         f: F,
@@ -135,10 +269,55 @@ impl Transaction {
         result
     }
 
+    /// Run the given closure in a read/write transaction and commit it, just
+    /// like [`update_and_commit`](Self::update_and_commit), but if the
+    /// transaction fails with an error that [`is_retryable`] (such as a
+    /// conflict with a concurrent transaction), roll back and try again in a
+    /// fresh transaction, up to `max_attempts` times in total.
+    ///
+    /// Before each retry we wait for `backoff` multiplied by the number of
+    /// attempts made so far. Genuine errors are returned immediately.
+    pub fn with_retry<T, F>(
+        connection: &Arc<DataStoreConnection>,
+        max_attempts: usize,
+        backoff: Duration,
+        mut f: F,
+    ) -> Result<T, ekg_error::Error>
+        where
+            F: FnMut(Arc<Transaction>) -> Result<T, ekg_error::Error>,
+    {
+        let mut attempt = 1_usize;
+        loop {
+            let result =
+                Self::begin_read_write(connection).and_then(|tx| tx.update_and_commit(&mut f));
+            match result {
+                Err(err) if attempt < max_attempts && is_retryable(&err) => {
+                    tracing::warn!(
+                        target: ekg_namespace::consts::LOG_TARGET_DATABASE,
+                        conn = connection.number,
+                        "Attempt {attempt} of {max_attempts} failed, retrying: {err}"
+                    );
+                    std::thread::sleep(backoff * attempt as u32);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
     pub fn commit(self: &Arc<Self>) -> Result<(), ekg_error::Error> {
         if !self.committed.load(std::sync::atomic::Ordering::Relaxed) {
             self.committed
                 .store(true, std::sync::atomic::Ordering::Relaxed);
+            let span = tracing::debug_span!(
+                target: ekg_namespace::consts::LOG_TARGET_DATABASE,
+                "commit",
+                txno = self.number,
+                conn = self.connection.number,
+                elapsed_ms = tracing::field::Empty,
+            );
+            let _entered = span.enter();
+            let started_at = std::time::Instant::now();
             tracing::trace!(
                 target: ekg_namespace::consts::LOG_TARGET_DATABASE,
                 "Committing {self:}"
@@ -146,6 +325,10 @@ impl Transaction {
             database_call!(CDataStoreConnection_commitTransaction(
                 self.connection.inner
             ))?;
+            self.connection
+                .transaction_open
+                .store(false, std::sync::atomic::Ordering::Relaxed);
+            span.record("elapsed_ms", started_at.elapsed().as_millis() as u64);
             tracing::trace!(
                 target: ekg_namespace::consts::LOG_TARGET_DATABASE,
                 "Committed {self:}",
@@ -154,6 +337,17 @@ impl Transaction {
         Ok(())
     }
 
+    /// Commit the transaction and report whether that changed the datastore.
+    pub fn commit_with_info(self: &Arc<Self>) -> Result<CommitInfo, ekg_error::Error> {
+        self.commit()?;
+        let version = self.connection.data_store_version().ok();
+        let changed = match (self.version_at_begin, version) {
+            (Some(before), Some(after)) => before != after,
+            _ => true,
+        };
+        Ok(CommitInfo { changed, version })
+    }
+
     pub fn rollback(self: &Arc<Self>) -> Result<(), ekg_error::Error> {
         if !self.committed.load(std::sync::atomic::Ordering::Relaxed) {
             self.committed
@@ -168,6 +362,9 @@ impl Transaction {
             database_call!(CDataStoreConnection_rollbackTransaction(
                 self.connection.inner
             ))?;
+            self.connection
+                .transaction_open
+                .store(false, std::sync::atomic::Ordering::Relaxed);
             tracing::debug!(
                 target: ekg_namespace::consts::LOG_TARGET_DATABASE,
                 txno = self.number,
@@ -194,6 +391,9 @@ impl Transaction {
             database_call!(CDataStoreConnection_rollbackTransaction(
                 self.connection.inner
             ))?;
+            self.connection
+                .transaction_open
+                .store(false, std::sync::atomic::Ordering::Relaxed);
             tracing::debug!(
                 target: ekg_namespace::consts::LOG_TARGET_DATABASE,
                 txno = self.number,
@@ -215,6 +415,22 @@ impl Transaction {
         result
     }
 
+    /// Same as [`update_and_commit`](Self::update_and_commit) but also
+    /// returning what the commit did.
+    pub fn update_and_commit_with_info<T, E: From<ekg_error::Error>, F>(
+        self: &Arc<Self>,
+        f: F,
+    ) -> Result<(T, CommitInfo), E>
+        where F: FnOnce(Arc<Transaction>) -> Result<T, E> {
+        match f(self.clone()) {
+            Ok(value) => Ok((value, self.commit_with_info()?)),
+            Err(err) => {
+                self.rollback()?;
+                Err(err)
+            }
+        }
+    }
+
     pub fn execute_and_rollback<T, F>(self: &Arc<Self>, f: F) -> Result<T, ekg_error::Error>
         where F: FnOnce(Arc<Transaction>) -> Result<T, ekg_error::Error> {
         let result = f(self.clone());
@@ -240,3 +456,33 @@ impl Transaction {
         result
     }
 }
+
+/// A transaction that was either started or joined by
+/// [`Transaction::begin_or_join`].
+#[derive(Debug)]
+pub struct TransactionScope {
+    tx: Arc<Transaction>,
+    outermost: bool,
+}
+
+impl Deref for TransactionScope {
+    type Target = Arc<Transaction>;
+
+    fn deref(&self) -> &Self::Target { &self.tx }
+}
+
+impl TransactionScope {
+    /// Return true if this scope started the transaction rather than joining
+    /// an already active one.
+    pub fn is_outermost(&self) -> bool { self.outermost }
+
+    /// Commit the transaction if this scope started it, otherwise leave it to
+    /// the outermost scope.
+    pub fn commit(self) -> Result<(), ekg_error::Error> {
+        if self.outermost {
+            self.tx.commit()
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+
+use {
+    crate::rdfox_api::CStatementResult,
+    std::fmt::{Display, Formatter},
+};
+
+/// What RDFox reported after evaluating an update statement.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateReport {
+    /// The number of facts that were inserted
+    pub inserted: u64,
+    /// The number of facts that were deleted
+    pub deleted:  u64,
+}
+
+impl Display for UpdateReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} facts inserted, {} facts deleted",
+            self.inserted, self.deleted
+        )
+    }
+}
+
+impl From<CStatementResult> for UpdateReport {
+    fn from(statement_result: CStatementResult) -> Self {
+        Self {
+            inserted: statement_result.numberOfInsertions as u64,
+            deleted:  statement_result.numberOfDeletions as u64,
+        }
+    }
+}
@@ -19,10 +19,13 @@ use {
     indoc::formatdoc,
     iref::Iri,
     rdfox_rs::{
+        APPLICATION_TRIG,
         DataStore,
         DataStoreConnection,
         FactDomain,
         GraphConnection,
+        GraphTarget,
+        is_connection_lost,
         Namespaces,
         Parameters,
         PersistenceMode,
@@ -30,6 +33,7 @@ use {
         Server,
         ServerConnection,
         Statement,
+        Term,
         Transaction,
     },
     // std::path::Path,
@@ -121,7 +125,7 @@ fn test_create_graph(
 
     Ok(GraphConnection::new(
         ds_connection.clone(),
-        test_graph,
+        GraphTarget::from(test_graph),
         None,
     ))
 }
@@ -158,7 +162,7 @@ fn test_cursor_with_lexical_value(
     graph_connection: &Arc<GraphConnection>,
 ) -> Result<(), ekg_error::Error> {
     tracing::info!("test_cursor_with_lexical_value");
-    let graph = graph_connection.graph.as_display_iri();
+    let graph = graph_connection.graph.graph().as_display_iri();
     let prefixes = Namespaces::empty()?;
     let query = Statement::new(
         &prefixes,
@@ -204,11 +208,55 @@ fn test_run_query_to_nquads_buffer(
         &nquads_query,
         APPLICATION_N_QUADS.deref(),
         None,
+        None,
     )?;
     tracing::info!("test_run_query_to_nquads_buffer passed");
     Ok(())
 }
 
+/// Return all quads in the store in N-Quads format, one per line, sorted
+/// so that they can be compared regardless of the order of export.
+fn sorted_n_quads(ds_connection: &Arc<DataStoreConnection>) -> Result<Vec<String>, ekg_error::Error> {
+    let nquads_query = Statement::nquads_query(&Namespaces::empty()?)?;
+    let mut buffer = Vec::new();
+    ds_connection.evaluate_to_n_quads(&mut buffer, &nquads_query)?;
+    let mut lines = String::from_utf8_lossy(buffer.as_slice())
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>();
+    lines.sort();
+    Ok(lines)
+}
+
+fn test_trig_round_trip(ds_connection: &Arc<DataStoreConnection>) -> Result<(), ekg_error::Error> {
+    tracing::info!("test_trig_round_trip");
+    let fixture = std::fs::File::open("tests/dataset.trig")?;
+    ds_connection.import_data_from_reader(
+        fixture,
+        APPLICATION_TRIG.deref(),
+        &GraphTarget::Default,
+    )?;
+    let before = sorted_n_quads(ds_connection)?;
+    assert!(before.iter().any(|quad| quad.ends_with("<https://example.com/dataset/people> .")));
+    assert!(before.iter().any(|quad| quad.ends_with("<https://example.com/dataset/places> .")));
+
+    let mut trig = Vec::new();
+    ds_connection.export_to_trig(&mut trig)?;
+    ds_connection.clear()?;
+    assert!(sorted_n_quads(ds_connection)?.is_empty());
+
+    ds_connection.import_data_from_reader(
+        trig.as_slice(),
+        APPLICATION_TRIG.deref(),
+        &GraphTarget::Default,
+    )?;
+    // The default graph and the named graphs must not have been merged
+    assert_eq!(sorted_n_quads(ds_connection)?, before);
+    tracing::info!("test_trig_round_trip passed");
+    Ok(())
+}
+
 pub fn get_concept(
     concept_id: &Literal,
     graph_connection: &Arc<GraphConnection>,
@@ -217,7 +265,7 @@ pub fn get_concept(
         .add_namespace(&PREFIX_CONCEPT)?
         .add_namespace(&PREFIX_SKOS)?;
 
-    let graph = graph_connection.graph.as_display_iri();
+    let graph = graph_connection.graph.graph().as_display_iri();
     let sparql = formatdoc! {
         r##"
             SELECT DISTINCT ?key ?label ?comment ?data_type ?rdfs_class ?predicate
@@ -277,6 +325,86 @@ fn test_query_concepts(
     Ok(())
 }
 
+/// A blank node in the pattern of `delete_pattern` would act as a variable
+/// and delete every match, so it has to be rejected without deleting anything.
+fn test_delete_pattern_rejects_blank_nodes(
+    ds_connection: &Arc<DataStoreConnection>,
+) -> Result<(), ekg_error::Error> {
+    tracing::info!("test_delete_pattern_rejects_blank_nodes");
+    let triples_before = ds_connection.count_triples(FactDomain::ASSERTED)?;
+    let result = ds_connection.delete_pattern(
+        Some(&Term::blank_node("b0")?),
+        None,
+        None,
+        &GraphTarget::Default,
+    );
+    assert!(result.is_err());
+    assert_eq!(
+        ds_connection.count_triples(FactDomain::ASSERTED)?,
+        triples_before
+    );
+    Ok(())
+}
+
+/// Drop a read/write transaction with uncommitted changes, together with its
+/// connection, and check that the changes are gone and that the datastore
+/// accepts a new read/write transaction from another connection.
+///
+/// The transaction keeps its connection alive so it is the transaction that
+/// rolls back here, the rollback in the `Drop` of the connection itself is
+/// only reached when a commit or rollback failed and isn't exercised.
+fn test_uncommitted_transaction_is_discarded(
+    server_connection: &Arc<ServerConnection>,
+) -> Result<(), ekg_error::Error> {
+    tracing::info!("test_uncommitted_transaction_is_discarded");
+    let parameters = Parameters::empty()?.persist_datastore(PersistenceMode::Off)?;
+    let conn = server_connection.create_data_store_named("drop-rollback", &parameters)?;
+    let data_store = conn.data_store.clone();
+    let tx = Transaction::begin_read_write(&conn)?;
+    GraphConnection::new(conn.clone(), GraphTarget::Default, None).insert(
+        &tx,
+        &Term::iri("https://example.com/s")?,
+        &Term::iri("https://example.com/p")?,
+        &Term::iri("https://example.com/o")?,
+    )?;
+    drop(conn);
+    // The transaction now holds the last reference to the connection, it's
+    // rolled back before the connection is destroyed
+    drop(tx);
+
+    let conn = server_connection.connect_to_data_store(&data_store)?;
+    assert_eq!(conn.count_triples(FactDomain::ALL)?, 0);
+    Transaction::begin_read_write(&conn)?.commit()?;
+    drop(conn);
+
+    server_connection.delete_data_store_named("drop-rollback")
+}
+
+/// Delete and recreate a datastore under an open connection, so that the
+/// connection is lost, and check that `with_reconnect` recovers from that.
+fn test_with_reconnect(
+    server_connection: &Arc<ServerConnection>,
+) -> Result<(), ekg_error::Error> {
+    tracing::info!("test_with_reconnect");
+    let parameters = Parameters::empty()?.persist_datastore(PersistenceMode::Off)?;
+    let conn = server_connection.create_data_store_named("reconnect", &parameters)?;
+    server_connection.delete_data_store_named("reconnect")?;
+    server_connection.create_data_store_named("reconnect", &parameters)?;
+
+    let err = conn.count_triples(FactDomain::ALL).unwrap_err();
+    assert!(is_connection_lost(&err), "not a lost connection: {err}");
+
+    let attempts = std::cell::Cell::new(0);
+    let count = conn.with_reconnect(|conn| {
+        attempts.set(attempts.get() + 1);
+        conn.count_triples(FactDomain::ALL)
+    });
+    assert_eq!(count?, 0);
+    assert_eq!(attempts.get(), 2);
+
+    server_connection.delete_data_store_named("reconnect")
+}
+
 /// Run the test with `RUST_LOG=info cargo test -- --nocapture` if you'd like to see what's going on.
 #[test_log::test]
 fn load_rdfox() -> Result<(), ekg_error::Error> {
@@ -314,6 +442,9 @@ fn load_rdfox() -> Result<(), ekg_error::Error> {
         })?;
         Transaction::begin_read_only(&conn)?
             .execute_and_rollback(|ref tx| test_query_concepts(tx, &graph_connection_meta))?;
+
+        test_trig_round_trip(&conn)?;
+        test_delete_pattern_rejects_blank_nodes(&conn)?;
     }
 
     std::thread::sleep(std::time::Duration::from_millis(500)); // wait for connection pool threads to end
@@ -322,6 +453,9 @@ fn load_rdfox() -> Result<(), ekg_error::Error> {
 
     server_connection.delete_data_store(&data_store)?;
 
+    test_with_reconnect(&server_connection)?;
+    test_uncommitted_transaction_is_discarded(&server_connection)?;
+
     tracing::info!("load_rdfox end");
 
     Ok(())
@@ -0,0 +1,40 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+// Checks that the prelude on its own is enough for the common use of the
+// crate, without starting an RDFox server.
+use rdfox_rs::prelude::*;
+
+#[allow(dead_code)]
+fn count_labels(
+    connection: &std::sync::Arc<DataStoreConnection>,
+    language: &str,
+) -> Result<usize, ekg_error::Error> {
+    let statement = Statement::new(
+        &Namespaces::default_namespaces()?,
+        "SELECT ?label WHERE { ?s rdfs:label ?label }".into(),
+    )?;
+    let mut cursor: Cursor = statement.cursor(
+        connection,
+        &Parameters::empty()?.fact_domain(FactDomain::ALL)?,
+    )?;
+    let mut count = 0;
+    Transaction::begin_read_only(connection)?.execute_and_rollback(|ref tx| {
+        cursor.consume(tx, 1000, |row: &CursorRow| {
+            let opened: &OpenedCursor = row.opened;
+            if opened.resolve_term_filtered(0, Some(language))?.is_some() {
+                count += 1;
+            }
+            Ok::<(), ekg_error::Error>(())
+        })
+    })?;
+    Ok(count)
+}
+
+#[test_log::test]
+fn test_prelude() {
+    let term = Term::iri("https://example.com/x").unwrap();
+    assert!(term.is_iri());
+    assert_eq!(TEXT_TURTLE.essence_str(), "text/turtle");
+    assert_eq!(APPLICATION_TRIG.essence_str(), "application/trig");
+    let _ = RoleCreds::default();
+}
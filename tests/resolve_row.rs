@@ -0,0 +1,110 @@
+// Copyright (c) 2018-2023, agnos.ai UK Ltd, all rights reserved.
+//---------------------------------------------------------------
+// Benchmark of `OpenedCursor::resolve_row` against resolving the columns of
+// a wide SELECT one by one. It's ignored by default since it loads a fair
+// amount of data, run it with:
+//
+//     cargo test --release --test resolve_row -- --ignored --nocapture
+//
+// with `RUST_LOG=info` to see the timings.
+use {
+    rdfox_rs::{prelude::*, PersistenceMode},
+    std::{fmt::Write, sync::Arc, time::Instant},
+};
+
+const ROWS: usize = 20_000;
+const COLUMNS: usize = 15;
+
+fn wide_data_store() -> Result<Arc<DataStoreConnection>, ekg_error::Error> {
+    let server = Server::start_with_parameters(
+        RoleCreds::default(),
+        Some(Parameters::empty()?.persist_datastore(PersistenceMode::Off)?),
+    )?;
+    let server_connection = server.connection_with_default_role()?;
+    let connection = server_connection.create_data_store_named(
+        "resolve_row",
+        &Parameters::empty()?.persist_datastore(PersistenceMode::Off)?,
+    )?;
+    let mut turtle = String::new();
+    for row in 0..ROWS {
+        write!(turtle, "<https://example.com/s{row}>").unwrap();
+        for column in 0..COLUMNS {
+            let object = match column % 3 {
+                0 => format!("\"value {row}\"@en"),
+                1 => format!("{row}"),
+                _ => format!("<https://example.com/o{row}>"),
+            };
+            write!(turtle, " <https://example.com/p{column}> {object} ;").unwrap();
+        }
+        writeln!(turtle, " a <https://example.com/Row> .").unwrap();
+    }
+    connection.import_data_from_reader(turtle.as_bytes(), &TEXT_TURTLE, &GraphTarget::Default)?;
+    Ok(connection)
+}
+
+fn wide_select() -> Result<Statement, ekg_error::Error> {
+    let mut sparql = String::from("SELECT ?s");
+    for column in 0..COLUMNS - 1 {
+        write!(sparql, " ?o{column}").unwrap();
+    }
+    sparql.push_str(" WHERE { ?s a <https://example.com/Row>");
+    for column in 0..COLUMNS - 1 {
+        write!(sparql, " ; <https://example.com/p{column}> ?o{column}").unwrap();
+    }
+    sparql.push_str(" }");
+    Statement::new(&Namespaces::empty()?, sparql.into())
+}
+
+/// Read all answers with the given function and return how long that took.
+fn time_all_rows<F>(
+    connection: &Arc<DataStoreConnection>,
+    cursor: &mut Cursor,
+    mut f: F,
+) -> Result<std::time::Duration, ekg_error::Error>
+    where F: FnMut(&CursorRow) -> Result<(), ekg_error::Error> {
+    let started_at = Instant::now();
+    let rows = Transaction::begin_read_only(connection)?
+        .execute_and_rollback(|ref tx| cursor.consume(tx, usize::MAX, &mut f))?;
+    assert_eq!(rows, ROWS);
+    Ok(started_at.elapsed())
+}
+
+#[test_log::test]
+#[ignore]
+fn bench_resolve_row() -> Result<(), ekg_error::Error> {
+    let connection = wide_data_store()?;
+    let mut cursor = wide_select()?.cursor(
+        &connection,
+        &Parameters::empty()?.fact_domain(FactDomain::ASSERTED)?,
+    )?;
+
+    let mut by_row = Vec::with_capacity(ROWS);
+    let row_duration = time_all_rows(&connection, &mut cursor, |row| {
+        by_row.push(row.opened.resolve_row()?);
+        Ok(())
+    })?;
+
+    let mut by_column = Vec::with_capacity(ROWS);
+    let column_duration = time_all_rows(&connection, &mut cursor, |row| {
+        by_column.push(
+            (0..row.opened.arity)
+                .map(|term_index| row.opened.resolve_term_filtered(term_index, None))
+                .collect::<Result<Vec<_>, _>>()?,
+        );
+        Ok(())
+    })?;
+
+    let literal_duration = time_all_rows(&connection, &mut cursor, |row| {
+        for term_index in 0..row.opened.arity {
+            row.lexical_value(term_index)?;
+        }
+        Ok(())
+    })?;
+
+    assert_eq!(by_row, by_column);
+    tracing::info!(
+        "{ROWS} rows of {COLUMNS} columns: resolve_row {row_duration:?}, resolve_term_filtered \
+         per column {column_duration:?}, lexical_value per column {literal_duration:?}"
+    );
+    Ok(())
+}